@@ -6,20 +6,41 @@
 //! and register it with the [`subcommand::Subcommand`] enum.
 
 mod profile;
+mod project;
+mod settings;
 mod subcommand;
 
+use crate::app::settings::Settings;
 use crate::app::subcommand::Subcommand;
+use crate::util::run::{Executor, Process};
+use carli::errorln;
 use carli::prelude::app::*;
-use std::{cell, io};
+use std::{cell, io, path, time};
 
 /// Manages the global command line options.
 #[derive(clap::Parser)]
 #[clap(about, version, author)]
 pub struct Application {
+    /// The path to the AWS CLI, if it is not the one found in PATH.
+    #[clap(long, global = true, env = "AWS_LOGIN_AWS_CLI", value_name = "PATH")]
+    aws_cli: Option<String>,
+
+    /// The flag used to indicate that a supported version of the AWS CLI is used.
+    #[clap(skip)]
+    aws_cli_checked: cell::Cell<bool>,
+
+    /// Prints the commands that would change something instead of running them.
+    #[clap(long, global = true)]
+    dry_run: bool,
+
     /// The error output stream.
     #[clap(skip = cell::RefCell::new(io::stderr().into()))]
     error: cell::RefCell<Stream>,
 
+    /// The executor used to run commands.
+    #[clap(skip = Box::new(Process) as Box<dyn Executor>)]
+    executor: Box<dyn Executor>,
+
     /// The input stream.
     #[clap(skip = cell::RefCell::new(io::stdin().into()))]
     input: cell::RefCell<Stream>,
@@ -28,6 +49,10 @@ pub struct Application {
     #[clap(skip = cell::RefCell::new(io::stdout().into()))]
     output: cell::RefCell<Stream>,
 
+    /// Records the commands that are run in a log file in the configuration directory.
+    #[clap(long, global = true)]
+    log: bool,
+
     /// Overrides the active AWS CLI profile.
     #[clap(long, global = true)]
     profile: Option<String>,
@@ -36,36 +61,198 @@ pub struct Application {
     #[clap(long, global = true)]
     region: Option<String>,
 
+    /// Whether the active AWS CLI profile configures a region, once it has been checked.
+    #[clap(skip)]
+    region_configured: cell::Cell<Option<bool>>,
+
+    /// The settings loaded from the settings file.
+    #[clap(skip)]
+    settings: Settings,
+
+    /// The path to the settings file.
+    #[clap(skip = settings::SETTINGS_FILE.clone())]
+    settings_file: path::PathBuf,
+
     /// The subcommand to execute.
     #[clap(subcommand)]
     subcommand: Subcommand,
+
+    /// Stops any command that has not finished after the number of seconds.
+    ///
+    /// The commands run by the exec subcommand and the spawn action are not stopped, since they
+    /// may be waiting on the user.
+    #[clap(long, global = true, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Shows the commands that are run (use twice to also show them before they are run).
+    #[clap(short, long, global = true, parse(from_occurrences))]
+    verbose: u8,
 }
 
 impl Application {
+    /// Returns the name of, or path to, the AWS CLI.
+    pub fn aws_cli(&self) -> &str {
+        self.aws_cli
+            .as_deref()
+            .or_else(|| self.settings.aws_cli())
+            .unwrap_or("aws")
+    }
+
+    /// Returns the flag used to indicate that a supported version of the AWS CLI is used.
+    pub fn aws_cli_checked(&self) -> bool {
+        self.aws_cli_checked.get()
+    }
+
+    /// Returns the flag used to indicate that commands are printed instead of being run.
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Returns the executor used to run commands.
+    pub fn executor(&self) -> &dyn Executor {
+        self.executor.as_ref()
+    }
+
+    /// Loads the settings from the settings file.
+    ///
+    /// The options provided on the command line take precedence over the loaded settings. If the
+    /// settings file could not be read, a warning is printed and the default settings are used so
+    /// that the other subcommands can still be used until the file is fixed.
+    pub fn load_settings(&mut self) -> Result<()> {
+        match settings::read_settings(&self.settings_file) {
+            Ok(settings) => self.settings = settings,
+            Err(error) => errorln!(
+                self,
+                "Warning: {}The default settings are used instead.",
+                error
+            )?,
+        }
+
+        Ok(())
+    }
+
+    /// Returns the flag used to indicate that the commands that are run are logged to a file.
+    pub fn log(&self) -> bool {
+        self.log
+    }
+
     /// Returns the name of the AWS CLI profile.
     pub fn profile(&self) -> Option<&str> {
         self.profile.as_deref()
     }
 
-    /// Returns the name of the AWS region.
+    /// Returns the name of the AWS region provided on the command line.
+    ///
+    /// The region setting is not used, since it only applies when the AWS CLI profile does not
+    /// configure its own region (see [`crate::util::run::Run::with_aws_options`]).
     pub fn region(&self) -> Option<&str> {
         self.region.as_deref()
     }
 
+    /// Returns whether the active AWS CLI profile configures a region, if it has been checked.
+    pub fn region_configured(&self) -> Option<bool> {
+        self.region_configured.get()
+    }
+
+    /// Returns the settings loaded from the settings file.
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Returns the path to the settings file.
+    pub fn settings_file(&self) -> &path::Path {
+        &self.settings_file
+    }
+
+    /// Returns the amount of time a command is given to finish before it is stopped.
+    pub fn timeout(&self) -> Option<time::Duration> {
+        self.timeout
+            .or_else(|| self.settings.timeout())
+            .map(time::Duration::from_secs)
+    }
+
+    /// Returns the level of verbosity used to show the commands that are run.
+    pub fn verbose(&self) -> u8 {
+        self.verbose
+    }
+
+    /// Remembers that a supported version of the AWS CLI is used.
+    pub fn set_aws_cli_checked(&self) {
+        self.aws_cli_checked.set(true);
+    }
+
+    /// Remembers whether the active AWS CLI profile configures a region.
+    pub fn set_region_configured(&self, configured: bool) {
+        self.region_configured.set(Some(configured));
+    }
+
     /// Creates a new test instance of the application.
     #[cfg(any(doc, test))]
     pub fn test(profile: Option<String>, region: Option<String>) -> Self {
         use subcommand::debug;
 
         Self {
+            aws_cli: None,
+            // The AWS CLI is not invoked by scripted commands, so its version is not checked.
+            aws_cli_checked: cell::Cell::new(true),
+            dry_run: false,
             error: cell::RefCell::new(Vec::new().into()),
+            executor: Box::new(Process),
             input: cell::RefCell::new(Vec::new().into()),
+            log: false,
             output: cell::RefCell::new(Vec::new().into()),
             profile,
             region,
+            region_configured: cell::Cell::new(None),
+            settings: Settings::default(),
+            settings_file: settings::SETTINGS_FILE.clone(),
             subcommand: Subcommand::Debug(debug::Subcommand::new(false)),
+            timeout: None,
+            verbose: 0,
         }
     }
+
+    /// Sets the flag used to indicate that commands are printed instead of being run.
+    #[cfg(test)]
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Replaces the executor used to run commands (e.g. with a [`crate::util::run::Script`]).
+    #[cfg(test)]
+    pub fn with_executor(mut self, executor: impl Executor + 'static) -> Self {
+        self.executor = Box::new(executor);
+        self
+    }
+
+    /// Replaces the settings loaded from the settings file.
+    #[cfg(test)]
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Replaces the path to the settings file.
+    #[cfg(test)]
+    pub fn with_settings_file(mut self, settings_file: &path::Path) -> Self {
+        self.settings_file = settings_file.to_owned();
+        self
+    }
+
+    /// Sets the amount of time, in seconds, a command is given to finish before it is stopped.
+    #[cfg(test)]
+    pub fn with_timeout(mut self, timeout: u64) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the level of verbosity used to show the commands that are run.
+    #[cfg(test)]
+    pub fn with_verbose(mut self, verbose: u8) -> Self {
+        self.verbose = verbose;
+        self
+    }
 }
 
 impl Main for Application {
@@ -75,15 +262,15 @@ impl Main for Application {
 }
 
 impl Shared for Application {
-    fn error(&self) -> cell::RefMut<Stream> {
+    fn error(&self) -> cell::RefMut<'_, Stream> {
         self.error.borrow_mut()
     }
 
-    fn input(&self) -> cell::RefMut<Stream> {
+    fn input(&self) -> cell::RefMut<'_, Stream> {
         self.input.borrow_mut()
     }
 
-    fn output(&self) -> cell::RefMut<Stream> {
+    fn output(&self) -> cell::RefMut<'_, Stream> {
         self.output.borrow_mut()
     }
 }
@@ -35,6 +35,18 @@ impl Profile {
     pub fn settings(&self) -> &collections::HashMap<String, String> {
         &self.settings
     }
+
+    /// Creates a new test instance of the profile.
+    #[cfg(test)]
+    pub fn test(name: &str, settings: &[(&str, &str)]) -> Self {
+        Self {
+            name: name.to_owned(),
+            settings: settings
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
 }
 
 /// A specialized [`Result`] type for a named collection of [`Profile`].
@@ -149,7 +161,7 @@ pub fn get_templates() -> Result<Templates> {
         return Ok(Templates::new());
     }
 
-    read_templates(&*TEMPLATES_FILE)
+    read_templates(&TEMPLATES_FILE)
 }
 
 /// Parses profile templates from the given stream reader.
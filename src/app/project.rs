@@ -0,0 +1,100 @@
+//! Manages finding and parsing of project files.
+//!
+//! A project file is a JSON encoded file named [`PROJECT_FILE`] that is placed in the root
+//! directory of a project. It names the AWS CLI profile, and optionally the region, that should be
+//! used while working in the directory or any of its subdirectories.
+//!
+//! ```json
+//! {
+//!     "profile": "dev-write",
+//!     "region": "us-east-1"
+//! }
+//! ```
+
+use carli::error::{Context, Error, Result};
+use std::{fs, io, path};
+
+/// The name of the project file.
+pub const PROJECT_FILE: &str = ".aws-login.json";
+
+/// Manages the settings found in a project file.
+#[derive(serde::Deserialize)]
+pub struct Project {
+    /// The name of the AWS CLI profile.
+    profile: String,
+
+    /// The name of the AWS region.
+    region: Option<String>,
+}
+
+impl Project {
+    /// Returns the name of the AWS CLI profile.
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Returns the name of the AWS region.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+}
+
+/// Finds the nearest project file by walking up from the given directory.
+///
+/// If none of the directories contain a project file, [`None`] is returned.
+pub fn find_project(dir: &path::Path) -> Option<path::PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Reads and parses a project from a JSON encoded file.
+pub fn read_project(path: &path::Path) -> Result<Project> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) => {
+            return Err(Error::from(error).context(format!(
+                "Could not read the project file: {}",
+                path.display()
+            )))
+        }
+    };
+
+    match serde_json::from_reader(io::BufReader::new(file)) {
+        Ok(project) => Ok(project),
+        Err(error) => Err(Error::new(1).message(format!("{}", error))),
+    }
+    .context(|| format!("Could not parse the project file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_and_read_project() {
+        let dir = std::env::temp_dir().join(format!("aws-login-project-{}", std::process::id()));
+        let nested = dir.join("a").join("b");
+
+        fs::create_dir_all(&nested).unwrap();
+
+        fs::write(dir.join(PROJECT_FILE), r#"{"profile": "dev-write"}"#).unwrap();
+
+        let path = find_project(&nested).unwrap();
+        let project = read_project(&path).unwrap();
+
+        assert_eq!(path, dir.join(PROJECT_FILE));
+        assert_eq!(project.profile(), "dev-write");
+        assert_eq!(project.region(), None);
+
+        fs::write(dir.join("a").join(PROJECT_FILE), "{}").unwrap();
+
+        assert_eq!(
+            find_project(&nested).unwrap(),
+            dir.join("a").join(PROJECT_FILE)
+        );
+        assert!(read_project(&dir.join("a").join(PROJECT_FILE)).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! Manages loading and saving of the application settings.
+//!
+//! The settings are JSON encoded in [`SETTINGS_FILE`] and provide the defaults used throughout the
+//! application. Any option provided on the command line takes precedence over its setting.
+//!
+//! ```json
+//! {
+//!     "auto_select": true,
+//!     "aws_cli": "/usr/local/bin/aws",
+//!     "rds_port": 3306,
+//!     "region": "us-east-1",
+//!     "shell": "zsh",
+//!     "template_urls": ["https://www.example.com/path/to/templates.json"],
+//!     "timeout": 300
+//! }
+//! ```
+
+use crate::util::config::CONFIG_DIR;
+use crate::util::shell::SHELLS;
+use carli::error::{Context, Error, Result};
+use carli::{err, error};
+use std::{fs, io, path};
+
+lazy_static::lazy_static! {
+    /// The path to the file containing the application settings.
+    pub static ref SETTINGS_FILE: path::PathBuf = CONFIG_DIR.join("config.json");
+}
+
+/// The names of the settings that can be managed.
+pub const KEYS: [&str; 7] = [
+    "auto_select",
+    "aws_cli",
+    "rds_port",
+    "region",
+    "shell",
+    "template_urls",
+    "timeout",
+];
+
+/// Manages the application settings.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+pub struct Settings {
+    /// The flag used to select the only available choice instead of prompting for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auto_select: Option<bool>,
+
+    /// The path to the AWS CLI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aws_cli: Option<String>,
+
+    /// The database port number used for RDS Proxy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rds_port: Option<u16>,
+
+    /// The name of the AWS region.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<String>,
+
+    /// The name of the preferred shell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell: Option<String>,
+
+    /// The URLs to download profile templates from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    template_urls: Vec<String>,
+
+    /// The number of seconds a command is given to finish before it is stopped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<u64>,
+}
+
+impl Settings {
+    /// Returns the flag used to select the only available choice instead of prompting for it.
+    pub fn auto_select(&self) -> bool {
+        self.auto_select.unwrap_or(false)
+    }
+
+    /// Returns the path to the AWS CLI.
+    pub fn aws_cli(&self) -> Option<&str> {
+        self.aws_cli.as_deref()
+    }
+
+    /// Returns the values of a setting, which are empty if it is not set.
+    ///
+    /// ```
+    /// let settings = read_settings(&SETTINGS_FILE)?;
+    ///
+    /// for url in settings.get("template_urls")? {
+    ///     println!("{}", url);
+    /// }
+    /// ```
+    pub fn get(&self, key: &str) -> Result<Vec<String>> {
+        Ok(match key {
+            "auto_select" => self.auto_select.iter().map(bool::to_string).collect(),
+            "aws_cli" => self.aws_cli.iter().cloned().collect(),
+            "rds_port" => self.rds_port.iter().map(u16::to_string).collect(),
+            "region" => self.region.iter().cloned().collect(),
+            "shell" => self.shell.iter().cloned().collect(),
+            "template_urls" => self.template_urls.clone(),
+            "timeout" => self.timeout.iter().map(u64::to_string).collect(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Returns the database port number used for RDS Proxy.
+    pub fn rds_port(&self) -> Option<u16> {
+        self.rds_port
+    }
+
+    /// Returns the name of the AWS region.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Sets the values of a setting.
+    ///
+    /// Only the `template_urls` setting accepts more than one value. The values are checked before
+    /// they are set so that an invalid settings file is not saved.
+    ///
+    /// ```
+    /// let mut settings = read_settings(&SETTINGS_FILE)?;
+    ///
+    /// settings.set("region", &["us-east-1".to_owned()])?;
+    ///
+    /// write_settings(&SETTINGS_FILE, &settings)?;
+    /// ```
+    pub fn set(&mut self, key: &str, values: &[String]) -> Result<()> {
+        if key == "template_urls" {
+            self.template_urls = values.to_vec();
+
+            return Ok(());
+        }
+
+        let value = match values {
+            [value] => value.as_str(),
+            _ => err!(1, "The setting, {}, requires exactly one value.", key),
+        };
+
+        match key {
+            "auto_select" => match value {
+                "true" => self.auto_select = Some(true),
+                "false" => self.auto_select = Some(false),
+                _ => err!(1, "The setting, {}, must be true or false.", key),
+            },
+            "aws_cli" => self.aws_cli = Some(value.to_owned()),
+            "rds_port" => match value.parse() {
+                Ok(port) => self.rds_port = Some(port),
+                Err(_) => err!(1, "The setting, {}, must be a port number.", key),
+            },
+            "region" => self.region = Some(value.to_owned()),
+            "shell" => {
+                if !SHELLS.contains(&value) {
+                    err!(
+                        1,
+                        "The shell, {}, is not supported. The supported shells are: {}",
+                        value,
+                        SHELLS.join(", ")
+                    );
+                }
+
+                self.shell = Some(value.to_owned());
+            }
+            "timeout" => match value.parse() {
+                Ok(timeout) => self.timeout = Some(timeout),
+                Err(_) => err!(1, "The setting, {}, must be a number of seconds.", key),
+            },
+            _ => return Err(unknown_key(key)),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name of the preferred shell.
+    pub fn shell(&self) -> Option<&str> {
+        self.shell.as_deref()
+    }
+
+    /// Returns the URLs to download profile templates from.
+    pub fn template_urls(&self) -> &[String] {
+        &self.template_urls
+    }
+
+    /// Returns the number of seconds a command is given to finish before it is stopped.
+    pub fn timeout(&self) -> Option<u64> {
+        self.timeout
+    }
+
+    /// Removes the values of a setting.
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        match key {
+            "auto_select" => self.auto_select = None,
+            "aws_cli" => self.aws_cli = None,
+            "rds_port" => self.rds_port = None,
+            "region" => self.region = None,
+            "shell" => self.shell = None,
+            "template_urls" => self.template_urls.clear(),
+            "timeout" => self.timeout = None,
+            _ => return Err(unknown_key(key)),
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads and parses the settings from a JSON encoded file.
+///
+/// If the file does not exist, the default settings are returned.
+pub fn read_settings(path: &path::Path) -> Result<Settings> {
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) => {
+            return Err(Error::from(error).context(format!(
+                "Could not read the settings file: {}",
+                path.display()
+            )))
+        }
+    };
+
+    match serde_json::from_reader(io::BufReader::new(file)) {
+        Ok(settings) => Ok(settings),
+        Err(error) => Err(error!(1, "{}", error)),
+    }
+    .context(|| format!("Could not parse the settings file: {}", path.display()))
+}
+
+/// Returns the error used for a setting that does not exist.
+fn unknown_key(key: &str) -> Error {
+    error!(
+        1,
+        "The setting, {}, does not exist. The settings are: {}",
+        key,
+        KEYS.join(", ")
+    )
+}
+
+/// Serializes the settings to a JSON encoded file.
+pub fn write_settings(path: &path::Path, settings: &Settings) -> Result<()> {
+    let file = match fs::File::create(path) {
+        Ok(file) => file,
+        Err(error) => {
+            return Err(Error::from(error).context(format!(
+                "Could not write to the settings file: {}",
+                path.display()
+            )))
+        }
+    };
+
+    let writer = io::BufWriter::new(file);
+
+    if let Err(error) = serde_json::to_writer_pretty(writer, settings) {
+        return Err(error!(1, "{}", error).context(format!(
+            "Could not serialize the settings to the local file: {}",
+            path.display()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read_and_write_settings() {
+        let path =
+            std::env::temp_dir().join(format!("aws-login-settings-{}.json", std::process::id()));
+
+        assert!(read_settings(&path).unwrap().region().is_none());
+
+        let mut settings = Settings::default();
+
+        settings.set("rds_port", &["3306".to_owned()]).unwrap();
+        settings.set("region", &["us-east-1".to_owned()]).unwrap();
+
+        write_settings(&path, &settings).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"rds_port\": 3306,\n  \"region\": \"us-east-1\"\n}"
+        );
+
+        let settings = read_settings(&path).unwrap();
+
+        assert_eq!(settings.rds_port(), Some(3306));
+        assert_eq!(settings.region(), Some("us-east-1"));
+
+        fs::write(&path, "[]").unwrap();
+
+        assert!(read_settings(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn set_and_unset() {
+        let mut settings = Settings::default();
+        let urls = [
+            "https://a.example.com".to_owned(),
+            "https://b.example.com".to_owned(),
+        ];
+
+        settings.set("auto_select", &["true".to_owned()]).unwrap();
+        settings.set("template_urls", &urls).unwrap();
+
+        assert!(settings.auto_select());
+        assert_eq!(settings.get("auto_select").unwrap(), ["true"]);
+        assert_eq!(settings.get("template_urls").unwrap(), urls);
+        assert!(settings.get("shell").unwrap().is_empty());
+
+        settings.unset("template_urls").unwrap();
+
+        assert!(settings.template_urls().is_empty());
+        assert_eq!(
+            format!("{}", settings.set("region", &urls).unwrap_err()),
+            "The setting, region, requires exactly one value.\n"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                settings.set("rds_port", &["x".to_owned()]).unwrap_err()
+            ),
+            "The setting, rds_port, must be a port number.\n"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                settings.set("timeout", &["1m".to_owned()]).unwrap_err()
+            ),
+            "The setting, timeout, must be a number of seconds.\n"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                settings.set("shell", &["cmd".to_owned()]).unwrap_err()
+            ),
+            format!(
+                "The shell, cmd, is not supported. The supported shells are: {}\n",
+                SHELLS.join(", ")
+            )
+        );
+        assert_eq!(
+            format!("{}", settings.unset("color").unwrap_err()),
+            "The setting, color, does not exist. The settings are: auto_select, aws_cli, rds_port, region, shell, template_urls, timeout\n"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! A subcommand used to remove the environment variables set by the application.
+
+use super::EnvOptions;
+use crate::app::Application;
+use crate::util::shell::{clear_tracked_vars, get_tracked_vars};
+use carli::errorln;
+use carli::prelude::cmd::*;
+
+/// The environment variables that are always removed from the shell environment.
+const DEFAULT_VARS: &[&str] = &["AWS_PROFILE", "AWS_REGION"];
+
+/// The options for the subcommand.
+#[derive(clap::Parser)]
+pub struct Subcommand {
+    /// The options used to modify the shell environment.
+    #[clap(flatten)]
+    env: EnvOptions,
+}
+
+impl Execute<Application> for Subcommand {
+    fn execute(&self, context: &Application) -> Result<()> {
+        match self.env.get_env(context)? {
+            Some(mut env) => {
+                let tracked = get_tracked_vars();
+
+                for name in DEFAULT_VARS {
+                    if !tracked.iter().any(|t| t == name) {
+                        env.unset_var(name).context(|| {
+                            format!("Could not remove the environment variable, {}.", name)
+                        })?;
+                    }
+                }
+
+                clear_tracked_vars(env.as_mut())
+                    .context(|| "Could not clear the environment variables.".to_owned())?;
+            }
+            None => {
+                errorln!(context, "Unable to automatically clear the environment.")?;
+                errorln!(context, "(Not integreated into the shell environment.)")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::shell::test::ENV_LOCK;
+    use std::env;
+    use std::io::Seek;
+
+    #[test]
+    fn environment_printed() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        env::set_var("AWS_LOGIN_VARS", "AWS_PROFILE EXAMPLE");
+
+        let context = Application::test(None, None);
+        let result = Subcommand {
+            env: EnvOptions {
+                print: true,
+                shell: Some("fish".to_owned()),
+            },
+        }
+        .execute(&context);
+
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert!(env::var("AWS_LOGIN_VARS").is_err());
+        assert_eq!(
+            output.to_string_lossy(),
+            "set -e AWS_REGION\nset -e AWS_PROFILE\nset -e EXAMPLE\nset -e AWS_LOGIN_VARS\n"
+        );
+    }
+}
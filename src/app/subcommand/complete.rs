@@ -0,0 +1,126 @@
+//! A subcommand used by shell completion scripts to list dynamic values.
+
+use super::{eks, profile};
+use crate::app::{profile as templates, Application};
+use carli::outputln;
+use carli::prelude::cmd::*;
+use std::str::FromStr;
+
+/// The kinds of values that can be completed.
+enum Kind {
+    /// The names of the EKS clusters for the active AWS CLI profile.
+    Clusters,
+
+    /// The names of the existing AWS CLI profiles and profile templates.
+    Profiles,
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clusters" => Ok(Self::Clusters),
+            "profiles" => Ok(Self::Profiles),
+            _ => Err(s.to_owned()),
+        }
+    }
+}
+
+/// The options for the subcommand.
+#[derive(clap::Parser)]
+pub struct Subcommand {
+    /// The kind of values to list (clusters, profiles).
+    kind: Kind,
+}
+
+impl Execute<Application> for Subcommand {
+    fn execute(&self, context: &Application) -> Result<()> {
+        // Completion must never print errors into the user's command line, so any failure to
+        // retrieve values (e.g. an expired session) simply results in no values.
+        let mut values = match self.kind {
+            Kind::Clusters => eks::get_clusters(context).unwrap_or_default(),
+            Kind::Profiles => {
+                let mut profiles = profile::get_existing_profiles(context).unwrap_or_default();
+
+                if let Ok(templates) = templates::get_profiles() {
+                    profiles.extend(templates.into_keys());
+                }
+
+                profiles
+            }
+        };
+
+        values.sort_unstable();
+        values.dedup();
+
+        for value in values {
+            outputln!(context, "{}", value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::run::Script;
+    use std::io::Seek;
+
+    /// The arguments used to list the clusters.
+    const LIST_ARGS: &[&str] = &[
+        "--profile",
+        "dev",
+        "eks",
+        "list-clusters",
+        "--output",
+        "json",
+    ];
+
+    #[test]
+    fn clusters_listed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output(r#"{"clusters": ["prod", "dev", "prod"]}"#),
+        );
+
+        let result = Subcommand {
+            kind: Kind::Clusters,
+        }
+        .execute(&context);
+
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(output.to_string_lossy(), "dev\nprod\n");
+    }
+
+    #[test]
+    fn clusters_not_listed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .error("The SSO session has expired.")
+                .status(255),
+        );
+
+        let result = Subcommand {
+            kind: Kind::Clusters,
+        }
+        .execute(&context);
+
+        let mut error = context.error();
+        let mut output = context.output();
+
+        error.rewind().unwrap();
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert!(error.to_string_lossy().is_empty());
+        assert!(output.to_string_lossy().is_empty());
+    }
+}
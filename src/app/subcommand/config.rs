@@ -0,0 +1,230 @@
+//! A subcommand used to manage the application settings.
+
+use crate::app::settings::{read_settings, write_settings, Settings, KEYS};
+use crate::app::Application;
+use carli::prelude::cmd::*;
+use carli::{err, outputln};
+
+/// The actions used to manage the settings.
+#[derive(clap::Subcommand)]
+enum Action {
+    /// Prints the value of a setting.
+    ///
+    /// If the setting has more than one value, each value is printed on its own line. If the
+    /// setting is not set, nothing is printed and the exit status is 1.
+    Get {
+        /// The name of the setting.
+        key: String,
+    },
+
+    /// Lists the settings that are set.
+    List,
+
+    /// Sets the value of a setting.
+    ///
+    /// Only the template_urls setting accepts more than one value, which replace all of its
+    /// existing values.
+    Set {
+        /// The name of the setting.
+        key: String,
+
+        /// The value of the setting.
+        #[clap(required = true)]
+        values: Vec<String>,
+    },
+
+    /// Removes the value of a setting.
+    Unset {
+        /// The name of the setting.
+        key: String,
+    },
+}
+
+/// The options for the subcommand.
+#[derive(clap::Parser)]
+pub struct Subcommand {
+    /// The action to perform.
+    #[clap(subcommand)]
+    action: Action,
+}
+
+/// Reads the settings from the settings file so that they can be changed.
+///
+/// If the settings file could not be read, it is not replaced, since the settings in it would be
+/// lost.
+fn get_settings(context: &Application) -> Result<Settings> {
+    read_settings(context.settings_file()).context(|| {
+        "The settings were not changed, since the settings file could not be read. Please fix or remove the file, and try again.".to_owned()
+    })
+}
+
+impl Execute<Application> for Subcommand {
+    fn execute(&self, context: &Application) -> Result<()> {
+        match &self.action {
+            Action::Get { key } => {
+                let values = context.settings().get(key)?;
+
+                if values.is_empty() {
+                    err!(1);
+                }
+
+                for value in values {
+                    outputln!(context, "{}", value)?;
+                }
+            }
+            Action::List => {
+                for key in KEYS {
+                    for value in context.settings().get(key)? {
+                        outputln!(context, "{}={}", key, value)?;
+                    }
+                }
+            }
+            Action::Set { key, values } => {
+                let mut settings = get_settings(context)?;
+
+                settings.set(key, values)?;
+
+                write_settings(context.settings_file(), &settings)
+                    .context(|| "Could not save the settings.".to_owned())?;
+            }
+            Action::Unset { key } => {
+                let mut settings = get_settings(context)?;
+
+                settings.unset(key)?;
+
+                write_settings(context.settings_file(), &settings)
+                    .context(|| "Could not save the settings.".to_owned())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use std::io::Seek;
+
+    /// Returns the path to a settings file used by a test.
+    fn settings_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("aws-login-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn list_settings() {
+        let mut settings = Settings::default();
+
+        settings.set("region", &["us-east-1".to_owned()]).unwrap();
+        settings
+            .set(
+                "template_urls",
+                &[
+                    "https://a.example.com".to_owned(),
+                    "https://b.example.com".to_owned(),
+                ],
+            )
+            .unwrap();
+
+        let context = Application::test(None, None).with_settings(settings);
+        let result = Subcommand {
+            action: Action::List,
+        }
+        .execute(&context);
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output.to_string_lossy(),
+            "region=us-east-1\ntemplate_urls=https://a.example.com\ntemplate_urls=https://b.example.com\n"
+        );
+    }
+
+    #[test]
+    fn malformed_settings_kept() {
+        let path = settings_file("malformed");
+
+        fs::write(&path, "{").unwrap();
+
+        let mut context = Application::test(None, None).with_settings_file(&path);
+
+        context.load_settings().unwrap();
+
+        let result = Subcommand {
+            action: Action::Set {
+                key: "region".to_owned(),
+                values: vec!["us-east-1".to_owned()],
+            },
+        }
+        .execute(&context);
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        assert_eq!(
+            error.to_string_lossy(),
+            format!(
+                "Warning: Could not parse the settings file: {}\n  EOF while parsing an object at line 1 column 1\nThe default settings are used instead.\n",
+                path.display()
+            )
+        );
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            format!(
+                "The settings were not changed, since the settings file could not be read. Please fix or remove the file, and try again.\n  Could not parse the settings file: {}\n    EOF while parsing an object at line 1 column 1\n",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn set_and_unset_saved() {
+        let path = settings_file("saved");
+        let mut context = Application::test(None, None).with_settings_file(&path);
+
+        context.load_settings().unwrap();
+
+        Subcommand {
+            action: Action::Set {
+                key: "rds_port".to_owned(),
+                values: vec!["3306".to_owned()],
+            },
+        }
+        .execute(&context)
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"rds_port\": 3306\n}"
+        );
+
+        Subcommand {
+            action: Action::Unset {
+                key: "rds_port".to_owned(),
+            },
+        }
+        .execute(&context)
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        let result = Subcommand {
+            action: Action::Set {
+                key: "rds_port".to_owned(),
+                values: vec!["x".to_owned()],
+            },
+        }
+        .execute(&context);
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,30 +1,89 @@
 //! A subcommand used to configure Docker to use the AWS Elastic Container Registry.
 
 use crate::app::Application;
-use crate::util::run::Run;
+use crate::util::run::{parse_json, Run};
 use carli::prelude::cmd::*;
 
+/// The output of the AWS CLI when getting the identity of the caller.
+#[derive(serde::Deserialize)]
+struct CallerIdentity {
+    /// The ID of the AWS account.
+    #[serde(rename = "Account")]
+    account: String,
+}
+
 /// The options for the subcommand.
 #[derive(clap::Parser)]
 pub struct Subcommand {}
 
 impl Execute<Application> for Subcommand {
     fn execute(&self, context: &Application) -> Result<()> {
-        let registry_uri = generate_registry_uri(context)?;
-        let password = Run::new("aws")
+        let mut identity = Run::aws(context);
+        let mut password = Run::aws(context);
+
+        identity
+            .with_aws_options(context)
+            .arg("sts")
+            .arg("get-caller-identity")
+            .arg("--output")
+            .arg("json")
+            .read_only();
+
+        password
             .with_aws_options(context)
             .arg("ecr")
             .arg("get-login-password")
-            .output()
-            .context(|| "Could not generate ECR password.".to_owned())?;
+            .read_only();
+
+        // None of the commands depend on each other, so they are run at the same time.
+        let (identity, password, region) = match context.region() {
+            Some(region) => {
+                let [identity, password] = Run::output_all(context, [identity, password]);
+
+                (identity, password, Ok(region.to_owned()))
+            }
+            None => {
+                let mut region = Run::aws(context);
+
+                // The region setting is only used if the profile does not configure a region.
+                if let Some(profile) = context.profile() {
+                    region.arg("--profile").arg(profile);
+                }
+
+                region.arg("configure").arg("get").arg("region").read_only();
+
+                let [identity, password, region] =
+                    Run::output_all(context, [identity, password, region]);
+
+                (identity, password, region)
+            }
+        };
+
+        let account = identity
+            .and_then(|output| parse_json::<CallerIdentity>(&output))
+            .context(|| "Could not get account ID from AWS CLI.".to_owned())?
+            .account;
+
+        // The AWS CLI fails if the region is not configured, so an error is the same as no region.
+        let region = match region
+            .ok()
+            .map(|output| output.trim().to_owned())
+            .filter(|region| !region.is_empty())
+            .or_else(|| context.settings().region().map(|region| region.to_owned()))
+        {
+            Some(region) => region,
+            None => err!(1, "The region could not be determined."),
+        };
+
+        let password = password.context(|| "Could not generate ECR password.".to_owned())?;
 
         Run::new("docker")
             .arg("login")
             .arg("--username")
             .arg("AWS")
-            .arg("--password")
-            .arg(&password)
-            .arg(&registry_uri)
+            .arg("--password-stdin")
+            .arg(&format!("{}.dkr.ecr.{}.amazonaws.com", account, region))
+            .input(&password)
             .pass_through(context)
             .context(|| "Docker could not be configured to use the registry.".to_owned())?;
 
@@ -32,39 +91,279 @@ impl Execute<Application> for Subcommand {
     }
 }
 
-/// Generates the ECR registry URI using the active profile.
-fn generate_registry_uri(context: &Application) -> Result<String> {
-    let account_id = Run::new("aws")
-        .with_aws_options(context)
-        .arg("sts")
-        .arg("get-caller-identity")
-        .arg("--query")
-        .arg("Account")
-        .arg("--output")
-        .arg("text")
-        .output()
-        .map(|output| output.trim().to_owned())
-        .context(|| "Could not get account ID from AWS CLI.".to_owned())?;
-
-    let region = match context.region() {
-        Some(region) => region.to_owned(),
-        None => {
-            let output = Run::new("aws")
-                .with_aws_options(context)
-                .arg("configure")
-                .arg("get")
-                .arg("region")
-                .output()
-                .map(|output| output.trim().to_owned())
-                .context(|| "Could not get default region from AWS CLI.".to_owned())?;
-
-            if output.is_empty() {
-                err!(1, "The region could not be determined.");
-            }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::settings::Settings;
+    use crate::util::run::Script;
+    use crate::util::shell::test::ENV_LOCK;
+    use std::env;
+    use std::io::Seek;
+
+    /// The arguments used to look up the account ID of the active profile.
+    const ACCOUNT_ARGS: &[&str] = &[
+        "--profile",
+        "dev",
+        "sts",
+        "get-caller-identity",
+        "--output",
+        "json",
+    ];
+
+    /// The identity of the caller returned by the AWS CLI.
+    const IDENTITY: &str = r#"{"UserId": "AROA:user", "Account": "123456789012"}"#;
+
+    #[test]
+    fn docker_login_with_password_stdin() {
+        let context = Application::test(Some("dev".to_owned()), Some("eu-west-1".to_owned()))
+            .with_executor(
+                Script::default()
+                    .expect(
+                        "aws",
+                        &[
+                            "--profile",
+                            "dev",
+                            "--region",
+                            "eu-west-1",
+                            "sts",
+                            "get-caller-identity",
+                            "--output",
+                            "json",
+                        ],
+                    )
+                    .output(IDENTITY)
+                    .expect(
+                        "aws",
+                        &[
+                            "--profile",
+                            "dev",
+                            "--region",
+                            "eu-west-1",
+                            "ecr",
+                            "get-login-password",
+                        ],
+                    )
+                    .output("secret")
+                    .expect(
+                        "docker",
+                        &[
+                            "login",
+                            "--username",
+                            "AWS",
+                            "--password-stdin",
+                            "123456789012.dkr.ecr.eu-west-1.amazonaws.com",
+                        ],
+                    )
+                    .input("secret")
+                    .output("Login Succeeded\n"),
+            );
+
+        let result = Subcommand {}.execute(&context);
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(output.to_string_lossy(), "Login Succeeded\n");
+    }
+
+    #[test]
+    fn docker_login_dry_run() {
+        let context = Application::test(Some("dev".to_owned()), Some("eu-west-1".to_owned()))
+            .with_dry_run(true)
+            .with_executor(
+                Script::default()
+                    .expect(
+                        "aws",
+                        &[
+                            "--profile",
+                            "dev",
+                            "--region",
+                            "eu-west-1",
+                            "sts",
+                            "get-caller-identity",
+                            "--output",
+                            "json",
+                        ],
+                    )
+                    .output(IDENTITY)
+                    .expect(
+                        "aws",
+                        &[
+                            "--profile",
+                            "dev",
+                            "--region",
+                            "eu-west-1",
+                            "ecr",
+                            "get-login-password",
+                        ],
+                    )
+                    .output("secret"),
+            );
+
+        let result = Subcommand {}.execute(&context);
+        let mut output = context.output();
 
-            output
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output.to_string_lossy(),
+            "docker login --username AWS --password-stdin 123456789012.dkr.ecr.eu-west-1.amazonaws.com <<< '<redacted>'\n"
+        );
+    }
+
+    #[test]
+    fn docker_login_failed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", ACCOUNT_ARGS)
+                .output(IDENTITY)
+                .expect("aws", &["--profile", "dev", "ecr", "get-login-password"])
+                .output("secret")
+                .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                .output("us-east-1\n")
+                .expect(
+                    "docker",
+                    &[
+                        "login",
+                        "--username",
+                        "AWS",
+                        "--password-stdin",
+                        "123456789012.dkr.ecr.us-east-1.amazonaws.com",
+                    ],
+                )
+                .input("secret")
+                .error("Cannot connect to the Docker daemon.\n")
+                .status(1),
+        );
+
+        let result = Subcommand {}.execute(&context);
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Docker could not be configured to use the registry.\n"
+        );
+        assert_eq!(
+            error.to_string_lossy(),
+            "Cannot connect to the Docker daemon.\n"
+        );
+    }
+
+    #[test]
+    fn password_not_generated() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", ACCOUNT_ARGS)
+                .output(IDENTITY)
+                .expect("aws", &["--profile", "dev", "ecr", "get-login-password"])
+                .error("Unable to locate credentials.")
+                .status(255)
+                .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                .output("us-east-1\n"),
+        );
+
+        let result = Subcommand {}.execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Could not generate ECR password.\n  Unable to locate credentials.\n"
+        );
+    }
+
+    #[test]
+    fn region_from_setting() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+        let names = ["AWS_REGION", "AWS_DEFAULT_REGION"];
+        let previous = names.map(env::var_os);
+
+        for name in names {
+            env::remove_var(name);
         }
-    };
 
-    Ok(format!("{}.dkr.ecr.{}.amazonaws.com", account_id, region))
+        let mut settings = Settings::default();
+
+        settings.set("region", &["eu-west-1".to_owned()]).unwrap();
+
+        // The profile is only checked for a region once for the commands that need the setting.
+        let context = Application::test(Some("dev".to_owned()), None)
+            .with_settings(settings)
+            .with_executor(
+                Script::default()
+                    .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                    .status(1)
+                    .expect(
+                        "aws",
+                        &[
+                            "--profile",
+                            "dev",
+                            "--region",
+                            "eu-west-1",
+                            "sts",
+                            "get-caller-identity",
+                            "--output",
+                            "json",
+                        ],
+                    )
+                    .output(IDENTITY)
+                    .expect(
+                        "aws",
+                        &[
+                            "--profile",
+                            "dev",
+                            "--region",
+                            "eu-west-1",
+                            "ecr",
+                            "get-login-password",
+                        ],
+                    )
+                    .output("secret")
+                    .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                    .status(1)
+                    .expect(
+                        "docker",
+                        &[
+                            "login",
+                            "--username",
+                            "AWS",
+                            "--password-stdin",
+                            "123456789012.dkr.ecr.eu-west-1.amazonaws.com",
+                        ],
+                    )
+                    .input("secret"),
+            );
+
+        let result = Subcommand {}.execute(&context);
+
+        for (name, value) in names.iter().zip(previous) {
+            if let Some(value) = value {
+                env::set_var(name, value);
+            }
+        }
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn region_not_determined() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", ACCOUNT_ARGS)
+                .output(IDENTITY)
+                .expect("aws", &["--profile", "dev", "ecr", "get-login-password"])
+                .output("secret")
+                .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                .status(1),
+        );
+
+        let result = Subcommand {}.execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "The region could not be determined.\n"
+        );
+    }
 }
@@ -5,6 +5,13 @@ use crate::util::run::Run;
 use crate::util::term::select;
 use carli::prelude::cmd::*;
 
+/// The output of the AWS CLI when listing the clusters.
+#[derive(serde::Deserialize)]
+struct Clusters {
+    /// The names of the clusters.
+    clusters: Vec<String>,
+}
+
 /// The options for the subcommand.
 #[derive(clap::Parser)]
 pub struct Subcommand {
@@ -23,11 +30,21 @@ impl Execute<Application> for Subcommand {
 
                 cluster
             }
-            None => select("Please select an EKS cluster to setup:", &clusters)
-                .context(|| "Unable to select an EKS cluster.".to_owned())?,
+            None => {
+                if clusters.is_empty() {
+                    err!(1, "No available EKS clusters were found.");
+                }
+
+                select(
+                    "Please select an EKS cluster to setup:",
+                    &clusters,
+                    context.settings().auto_select(),
+                )
+                .context(|| "Unable to select an EKS cluster.".to_owned())?
+            }
         };
 
-        Run::new("aws")
+        Run::aws(context)
             .with_aws_options(context)
             .arg("eks")
             .arg("update-kubeconfig")
@@ -41,22 +58,152 @@ impl Execute<Application> for Subcommand {
 }
 
 /// Retrieves the list of clusters available in EKS for the active AWS CLI profile.
-fn get_clusters(context: &Application) -> Result<Vec<String>> {
-    let clusters = Run::new("aws")
+pub fn get_clusters(context: &Application) -> Result<Vec<String>> {
+    let clusters = Run::aws(context)
         .with_aws_options(context)
         .arg("eks")
         .arg("list-clusters")
-        .arg("--query")
-        .arg("clusters")
-        .arg("--output")
-        .arg("text")
-        .output()
+        .read_only()
+        .json::<Clusters>(context)
         .context(|| {
             "The list of available EKS clusters could not be retrieved from the AWS CLI.".to_owned()
-        })?
-        .split_whitespace()
-        .map(|s| s.to_owned())
-        .collect();
+        })?;
+
+    Ok(clusters.clusters)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::run::Script;
+    use std::io::Seek;
+
+    /// The arguments used to list the available clusters.
+    const LIST_ARGS: &[&str] = &[
+        "--profile",
+        "dev",
+        "eks",
+        "list-clusters",
+        "--output",
+        "json",
+    ];
+
+    /// The clusters listed by the AWS CLI.
+    const CLUSTERS: &str = r#"{"clusters": ["apps", "data"]}"#;
+
+    #[test]
+    fn cluster_not_available() {
+        let context = Application::test(Some("dev".to_owned()), None)
+            .with_executor(Script::default().expect("aws", LIST_ARGS).output(CLUSTERS));
+
+        let result = Subcommand {
+            cluster: Some("missing".to_owned()),
+        }
+        .execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "The specified cluster is not available.\n"
+        );
+    }
+
+    #[test]
+    fn clusters_not_found() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output(r#"{"clusters": []}"#),
+        );
+
+        let result = Subcommand { cluster: None }.execute(&context);
 
-    Ok(clusters)
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "No available EKS clusters were found.\n"
+        );
+    }
+
+    #[test]
+    fn clusters_not_listed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .error("The SSO session associated with this profile has expired.")
+                .status(255),
+        );
+
+        let result = get_clusters(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "The list of available EKS clusters could not be retrieved from the AWS CLI.\n  The SSO session associated with this profile has expired.\n"
+        );
+    }
+
+    #[test]
+    fn kubeconfig_updated() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output(CLUSTERS)
+                .expect(
+                    "aws",
+                    &[
+                        "--profile",
+                        "dev",
+                        "eks",
+                        "update-kubeconfig",
+                        "--name",
+                        "data",
+                    ],
+                )
+                .output("Updated context data in /home/user/.kube/config\n"),
+        );
+
+        let result = Subcommand {
+            cluster: Some("data".to_owned()),
+        }
+        .execute(&context);
+
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output.to_string_lossy(),
+            "Updated context data in /home/user/.kube/config\n"
+        );
+    }
+
+    #[test]
+    fn kubeconfig_not_updated() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output(CLUSTERS)
+                .expect(
+                    "aws",
+                    &[
+                        "--profile",
+                        "dev",
+                        "eks",
+                        "update-kubeconfig",
+                        "--name",
+                        "apps",
+                    ],
+                )
+                .status(255),
+        );
+
+        let result = Subcommand {
+            cluster: Some("apps".to_owned()),
+        }
+        .execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Could not get the AWS CLI to configure kubectl.\n"
+        );
+    }
 }
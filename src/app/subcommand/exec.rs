@@ -0,0 +1,89 @@
+//! A subcommand used to run a command using an AWS CLI profile.
+
+use super::profile::resolve_profile_and_region;
+use crate::app::Application;
+use crate::util::run::Run;
+use carli::prelude::cmd::*;
+
+/// The options for the subcommand.
+#[derive(clap::Parser)]
+pub struct Subcommand {
+    /// The command to run, followed by its arguments.
+    #[clap(last = true, required = true)]
+    command: Vec<String>,
+}
+
+impl Execute<Application> for Subcommand {
+    fn execute(&self, context: &Application) -> Result<()> {
+        let (profile, region) = resolve_profile_and_region(context)?;
+
+        let mut run = Run::new(&self.command[0]);
+
+        for arg in &self.command[1..] {
+            run.arg(arg);
+        }
+
+        run.env("AWS_PROFILE", &profile);
+
+        if let Some(region) = region {
+            run.env("AWS_REGION", &region);
+        }
+
+        // The exit status of the command is used as our own, so no context is added to the error
+        // that would otherwise be printed after the command's own output.
+        run.interactive(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::run::Script;
+
+    /// The arguments used to list the existing profiles.
+    const LIST_ARGS: &[&str] = &["configure", "list-profiles"];
+
+    #[test]
+    fn command_failed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output("dev\n")
+                .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                .status(1)
+                .expect("make", &["deploy"])
+                .env("AWS_PROFILE", "dev")
+                .status(3),
+        );
+
+        let result = Subcommand {
+            command: vec!["make".to_owned(), "deploy".to_owned()],
+        }
+        .execute(&context);
+
+        let error = result.unwrap_err();
+
+        assert!(format!("{:?}", error).contains("status: 3"));
+        assert_eq!(format!("{}", error), "");
+    }
+
+    #[test]
+    fn command_run() {
+        let context = Application::test(Some("dev".to_owned()), Some("us-east-1".to_owned()))
+            .with_executor(
+                Script::default()
+                    .expect("aws", LIST_ARGS)
+                    .output("dev\nprod\n")
+                    .expect("make", &["deploy", "--force"])
+                    .env("AWS_PROFILE", "dev")
+                    .env("AWS_REGION", "us-east-1"),
+            );
+
+        let result = Subcommand {
+            command: vec!["make".to_owned(), "deploy".to_owned(), "--force".to_owned()],
+        }
+        .execute(&context);
+
+        assert!(result.is_ok());
+    }
+}
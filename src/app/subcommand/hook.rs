@@ -0,0 +1,244 @@
+//! A subcommand used by the shell integration to switch profiles when changing directories.
+
+use crate::app::project::{find_project, read_project};
+use crate::app::Application;
+use crate::util::shell::{get_env, set_tracked_var, Environment, SUBSHELL_PROFILE};
+use carli::errorln;
+use carli::prelude::cmd::*;
+use std::collections::HashMap;
+use std::{env, path};
+
+/// The name of the environment variable used to record the path to the active project file.
+const PROJECT_VAR: &str = "AWS_LOGIN_PROJECT";
+
+/// The name of the environment variable used to record the values to restore.
+///
+/// The values of [`RESTORED_VARS`] from before a project was entered are recorded as a JSON
+/// encoded object so that they can be restored once the project has been left.
+const RESTORE_VAR: &str = "AWS_LOGIN_RESTORE";
+
+/// The environment variables that are restored once a project has been left.
+const RESTORED_VARS: &[&str] = &["AWS_PROFILE", "AWS_REGION"];
+
+/// The options for the subcommand.
+#[derive(clap::Parser)]
+pub struct Subcommand {}
+
+impl Execute<Application> for Subcommand {
+    fn execute(&self, context: &Application) -> Result<()> {
+        // A subshell is bound to the profile it was started for, so projects are ignored in it.
+        if env::var(SUBSHELL_PROFILE).is_ok() {
+            return Ok(());
+        }
+
+        // The hook is invoked when the prompt is shown, so nothing is done without the integration.
+        let mut env = match get_env() {
+            Some(env) => env,
+            None => return Ok(()),
+        };
+
+        match env::current_dir() {
+            Ok(dir) => switch(context, env.as_mut(), &dir),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Returns the values recorded before the active project was entered.
+fn get_restore() -> HashMap<String, Option<String>> {
+    env::var(RESTORE_VAR)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Restores the recorded value of an environment variable, or removes it if it was not set.
+fn restore_var(
+    environment: &mut dyn Environment,
+    restore: &HashMap<String, Option<String>>,
+    name: &str,
+) -> Result<()> {
+    match restore.get(name) {
+        Some(Some(value)) => environment.set_var(name, value),
+        _ => environment.unset_var(name),
+    }
+}
+
+/// Switches to the profile of the project found from the directory.
+///
+/// If a project is not found but one was active, the values recorded before it was entered are
+/// restored instead. The profile and region of a project are tracked so that they are removed by
+/// the clear subcommand like the ones set by the use subcommand.
+fn switch(
+    context: &Application,
+    environment: &mut dyn Environment,
+    dir: &path::Path,
+) -> Result<()> {
+    let found = find_project(dir);
+    let active = env::var(PROJECT_VAR).ok();
+
+    match (found, active) {
+        (Some(path), active) => {
+            let path_string = path.to_string_lossy();
+
+            if active.as_deref() == Some(&*path_string) {
+                return Ok(());
+            }
+
+            let project = read_project(&path)?;
+            let restore = if active.is_some() {
+                get_restore()
+            } else {
+                let restore = RESTORED_VARS
+                    .iter()
+                    .map(|name| (name.to_string(), env::var(name).ok()))
+                    .collect::<HashMap<String, Option<String>>>();
+
+                environment.set_var(
+                    RESTORE_VAR,
+                    &serde_json::to_string(&restore).unwrap_or_default(),
+                )?;
+
+                restore
+            };
+
+            set_tracked_var(environment, "AWS_PROFILE", project.profile())?;
+
+            match project.region() {
+                Some(region) => set_tracked_var(environment, "AWS_REGION", region)?,
+                None => restore_var(environment, &restore, "AWS_REGION")?,
+            }
+
+            environment.set_var(PROJECT_VAR, &path_string)?;
+
+            errorln!(
+                context,
+                "aws-login: Using the AWS CLI profile, {}, for this project.",
+                project.profile()
+            )?;
+        }
+        (None, Some(_)) => {
+            let restore = get_restore();
+
+            for name in RESTORED_VARS {
+                restore_var(environment, &restore, name)?;
+            }
+
+            environment.unset_var(PROJECT_VAR)?;
+            environment.unset_var(RESTORE_VAR)?;
+
+            errorln!(context, "aws-login: Restored the previous AWS CLI profile.")?;
+        }
+        (None, None) => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::shell::test::ENV_LOCK;
+    use std::fs;
+    use std::io::Seek;
+
+    /// The environment variables changed by the hook.
+    const VARS: &[&str] = &[
+        "AWS_LOGIN_PROJECT",
+        "AWS_LOGIN_RESTORE",
+        "AWS_LOGIN_VARS",
+        "AWS_PROFILE",
+        "AWS_REGION",
+    ];
+
+    /// Applies the changes to the environment of the current process, like the shell would.
+    #[derive(Default)]
+    struct Shell {
+        /// The number of changes that were applied.
+        changes: usize,
+    }
+
+    impl Environment for Shell {
+        fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
+            self.changes += 1;
+
+            env::set_var(name, value);
+
+            Ok(())
+        }
+
+        fn unset_var(&mut self, name: &str) -> Result<()> {
+            self.changes += 1;
+
+            env::remove_var(name);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn enter_and_leave_project() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+        let dir = env::temp_dir().join(format!("aws-login-hook-{}", std::process::id()));
+        let project = dir.join("project");
+        let other = dir.join("other");
+
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(
+            project.join(".aws-login.json"),
+            r#"{"profile": "dev", "region": "us-west-2"}"#,
+        )
+        .unwrap();
+
+        for name in VARS {
+            env::remove_var(name);
+        }
+
+        env::set_var("AWS_PROFILE", "default");
+
+        let context = Application::test(None, None);
+        let mut shell = Shell::default();
+
+        switch(&context, &mut shell, &project.join("src")).unwrap();
+
+        assert_eq!(env::var("AWS_PROFILE").unwrap(), "dev");
+        assert_eq!(env::var("AWS_REGION").unwrap(), "us-west-2");
+        assert_eq!(
+            env::var("AWS_LOGIN_VARS").unwrap(),
+            "AWS_PROFILE AWS_REGION"
+        );
+        assert_eq!(
+            env::var("AWS_LOGIN_PROJECT").unwrap(),
+            project.join(".aws-login.json").to_string_lossy()
+        );
+
+        let changes = shell.changes;
+
+        switch(&context, &mut shell, &project).unwrap();
+
+        assert_eq!(shell.changes, changes);
+
+        switch(&context, &mut shell, &other).unwrap();
+
+        assert_eq!(env::var("AWS_PROFILE").unwrap(), "default");
+        assert!(env::var("AWS_REGION").is_err());
+        assert!(env::var("AWS_LOGIN_PROJECT").is_err());
+        assert!(env::var("AWS_LOGIN_RESTORE").is_err());
+
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        assert_eq!(
+            error.to_string_lossy(),
+            "aws-login: Using the AWS CLI profile, dev, for this project.\naws-login: Restored the previous AWS CLI profile.\n"
+        );
+
+        for name in VARS {
+            env::remove_var(name);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
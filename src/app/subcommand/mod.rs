@@ -2,17 +2,81 @@
 
 pub mod debug;
 
+mod clear;
+mod complete;
+mod config;
 mod ecr;
 mod eks;
+mod exec;
+mod hook;
 mod profile;
+mod prompt;
 mod pull;
 mod rds;
 mod shell;
 mod sso;
 
 use crate::app::Application;
+use crate::util::shell::{detect_shell, get_env, get_print_env, Environment, SHELLS};
+use carli::error;
 use carli::prelude::cmd::*;
 
+/// The options for subcommands that modify the shell environment.
+#[derive(clap::Args)]
+pub struct EnvOptions {
+    /// Prints the shell code used to modify the environment instead of using the integration.
+    ///
+    /// The printed shell code can be evaluated without the shell integration being installed,
+    /// which is useful for scripts, Makefiles, and tools such as direnv. If a shell is not provided
+    /// using --shell, the shell setting is used, or the shell is detected.
+    ///
+    /// eval "$(aws-login use --profile dev --print -s bash)"
+    #[clap(long)]
+    print: bool,
+
+    /// The name of the shell to print the shell code for (e.g. bash).
+    #[clap(short, long, requires = "print")]
+    shell: Option<String>,
+}
+
+impl EnvOptions {
+    /// Returns the [`Environment`] used to modify the shell environment.
+    ///
+    /// If printing was requested, the environment will print shell code for the provided,
+    /// preferred, or detected shell. Otherwise, the environment managed by the shell integration is
+    /// used, and [`None`] is returned if the application is not integrated into the shell
+    /// environment.
+    pub fn get_env<'a>(
+        &self,
+        context: &'a Application,
+    ) -> Result<Option<Box<dyn Environment + 'a>>> {
+        if !self.print {
+            return Ok(get_env());
+        }
+
+        let shell = match self.shell.as_deref().or_else(|| context.settings().shell()) {
+            Some(shell) => shell,
+            None => detect_shell().ok_or_else(|| {
+                error!(
+                    1,
+                    "The shell could not be detected, please use --shell with one of: {}",
+                    SHELLS.join(", ")
+                )
+            })?,
+        };
+
+        match get_print_env(shell, context) {
+            Some(env) => Ok(Some(env)),
+            None => Err(error!(
+                1,
+                "The shell, {}, is not supported. The supported shells are: {}",
+                shell,
+                SHELLS.join(", ")
+            )),
+        }
+    }
+}
+
 /// The subcommands available to the user.
 #[derive(clap::Parser)]
 pub enum Subcommand {
@@ -20,6 +84,27 @@ pub enum Subcommand {
     #[cfg(debug_assertions)]
     Debug(debug::Subcommand),
 
+    /// Removes the environment variables set by the application.
+    ///
+    /// This subcommand will remove AWS_PROFILE, AWS_REGION, and any other environment variable
+    /// that was set by the application in the current shell session. Once the variables have been
+    /// removed, the AWS CLI will go back to using the default profile and region.
+    Clear(clear::Subcommand),
+
+    /// Lists values for shell completion scripts.
+    #[clap(hide = true)]
+    Complete(complete::Subcommand),
+
+    /// Manages the application settings.
+    ///
+    /// The settings provide defaults used throughout the application, such as the AWS region, the
+    /// preferred shell, the path to the AWS CLI, and the URLs to download profile templates from.
+    /// They are stored in config.json (~/.config/aws-login/ or %APPDATA%\Roaming\AWS Login\). Any
+    /// option provided on the command line takes precedence over its setting.
+    ///
+    /// The settings are: auto_select, aws_cli, rds_port, region, shell, template_urls, timeout
+    Config(config::Subcommand),
+
     /// Configures Docker to use AWS ECR.
     ///
     /// This subcommand will generate the registry URI for the account in your active AWS CLI
@@ -34,6 +119,20 @@ pub enum Subcommand {
     /// will be updated to support accessing it.
     Eks(eks::Subcommand),
 
+    /// Runs a command using an AWS CLI profile.
+    ///
+    /// This subcommand will select (or create) an AWS CLI profile the same way that the use
+    /// subcommand does, and then run the command with AWS_PROFILE and AWS_REGION set for it. This
+    /// does not require the shell integration, which makes it suitable for scripts and tools that
+    /// cannot use it. The exit status of the command is used as the exit status of the subcommand.
+    ///
+    /// aws-login exec --profile my-profile -- aws s3 ls
+    Exec(exec::Subcommand),
+
+    /// Switches the AWS CLI profile when entering or leaving a project directory.
+    #[clap(hide = true)]
+    Hook(hook::Subcommand),
+
     /// Makes an AWS CLI profile the active profile.
     ///
     /// This subcommand will first check if the profile exists. If the profile does not exist but
@@ -43,6 +142,14 @@ pub enum Subcommand {
     #[clap(name = "use")]
     Profile(profile::Subcommand),
 
+    /// Prints a segment for the shell prompt.
+    ///
+    /// This subcommand will print a short segment (e.g. dev-write@us-east-1 (42m)) describing the
+    /// active AWS CLI profile, the region, and the time left before the SSO session expires. The
+    /// AWS CLI configuration and SSO token cache are read directly so that it is fast enough to be
+    /// used every time the prompt is rendered. See `shell prompt` to add it to your prompt.
+    Prompt(prompt::Subcommand),
+
     /// Downloads profile templates from a URL.
     ///
     /// This subcommand will download profile templates from a URL and store them in the local
@@ -80,9 +187,15 @@ pub enum Subcommand {
 impl Execute<Application> for Subcommand {
     fn execute(&self, context: &Application) -> Result<()> {
         match self {
+            Self::Clear(cmd) => cmd.execute(context),
+            Self::Complete(cmd) => cmd.execute(context),
+            Self::Config(cmd) => cmd.execute(context),
             Self::Ecr(cmd) => cmd.execute(context),
             Self::Eks(cmd) => cmd.execute(context),
+            Self::Exec(cmd) => cmd.execute(context),
+            Self::Hook(cmd) => cmd.execute(context),
             Self::Profile(cmd) => cmd.execute(context),
+            Self::Prompt(cmd) => cmd.execute(context),
             Self::Pull(cmd) => cmd.execute(context),
             Self::Rds(cmd) => cmd.execute(context),
             Self::Shell(cmd) => cmd.execute(context),
@@ -1,50 +1,39 @@
 //! A subcommand used to create and/or select an AWS CLI profile.
 
+use super::EnvOptions;
 use crate::app::{profile, Application};
 use crate::util::run::Run;
-use crate::util::shell::get_env;
+use crate::util::shell::set_tracked_var;
 use crate::util::term::select;
 use carli::errorln;
 use carli::prelude::cmd::*;
 
+/// The profile settings that are redacted when the commands used to set them are displayed.
+const SECRET_SETTINGS: &[&str] = &[
+    "aws_access_key_id",
+    "aws_secret_access_key",
+    "aws_session_token",
+];
+
 /// The options for the subcommand.
 #[derive(clap::Parser)]
-pub struct Subcommand {}
+pub struct Subcommand {
+    /// The options used to modify the shell environment.
+    #[clap(flatten)]
+    env: EnvOptions,
+}
 
 impl Execute<Application> for Subcommand {
     fn execute(&self, context: &Application) -> Result<()> {
-        let existing = get_existing_profiles(context)?;
-        let profiles = profile::get_profiles()?;
-        let profile = match context.profile() {
-            Some(profile) => profile.to_owned(),
-            None => {
-                let mut merged = profiles
-                    .keys()
-                    .map(|s| s.as_str())
-                    .chain(existing.iter().map(|s| s.as_str()))
-                    .collect::<Vec<&str>>();
-
-                merged.sort_unstable();
-                merged.dedup();
-
-                if merged.is_empty() {
-                    err!(1, "There are no profiles available to choose from.");
-                }
-
-                select("Please select a profile to use:", &merged)?.to_string()
-            }
-        };
+        let profile = resolve_profile(context)?;
 
-        if !existing.iter().any(|p| p == profile.as_str()) {
-            if let Some(profile) = profiles.get(&profile) {
-                create_profile(context, profile)?;
-            } else {
-                err!(1, "The profile, {}, does not exist.", profile);
-            }
+        // The profile may not have been created, so the shell environment is left as it is.
+        if context.dry_run() {
+            return Ok(());
         }
 
-        match get_env() {
-            Some(mut env) => env.set_var("AWS_PROFILE", &profile)?,
+        match self.env.get_env(context)? {
+            Some(mut env) => set_tracked_var(env.as_mut(), "AWS_PROFILE", &profile)?,
             None => {
                 errorln!(context, "Unable to automatically switch AWS CLI profiles.")?;
                 errorln!(context, "(Not integreated into the shell environment.)")?;
@@ -58,14 +47,21 @@ impl Execute<Application> for Subcommand {
 /// Creates the AWS CLI profile.
 fn create_profile(context: &Application, profile: &profile::Profile) -> Result<()> {
     for (key, value) in profile.settings() {
-        Run::new("aws")
-            .arg("--profile")
+        let mut run = Run::aws(context);
+
+        run.arg("--profile")
             .arg(profile.name())
             .arg("configure")
             .arg("set")
-            .arg(key)
-            .arg(value)
-            .pass_through(context)
+            .arg(key);
+
+        if SECRET_SETTINGS.contains(&key.as_str()) {
+            run.secret_arg(value);
+        } else {
+            run.arg(value);
+        }
+
+        run.pass_through(context)
             .context(|| format!("Could not set the profile setting, {}.", key))?;
     }
 
@@ -73,12 +69,43 @@ fn create_profile(context: &Application, profile: &profile::Profile) -> Result<(
 }
 
 /// Returns a list of existing AWS CLI profiles.
-fn get_existing_profiles(context: &Application) -> Result<Vec<String>> {
-    let profiles = Run::new("aws")
-        .with_aws_options(context)
+pub fn get_existing_profiles(context: &Application) -> Result<Vec<String>> {
+    parse_profiles(list_profiles(context).output(context))
+}
+
+/// Returns the region configured for the AWS CLI profile, if any.
+pub fn get_profile_region(context: &Application, profile: &str) -> Option<String> {
+    parse_region(get_region(context, profile).output(context))
+}
+
+/// Returns the command used to get the region configured for an AWS CLI profile.
+fn get_region(context: &Application, profile: &str) -> Run {
+    let mut run = Run::aws(context);
+
+    run.arg("--profile")
+        .arg(profile)
         .arg("configure")
-        .arg("list-profiles")
-        .output()
+        .arg("get")
+        .arg("region")
+        .read_only();
+
+    run
+}
+
+/// Returns the command used to list the existing AWS CLI profiles.
+fn list_profiles(context: &Application) -> Run {
+    let mut run = Run::aws(context);
+
+    // The profile option is not added, since the AWS CLI refuses to list the profiles if the given
+    // profile does not exist yet (e.g. it is about to be created from a template).
+    run.arg("configure").arg("list-profiles").read_only();
+
+    run
+}
+
+/// Parses the output of the command used to list the existing AWS CLI profiles.
+fn parse_profiles(output: Result<String>) -> Result<Vec<String>> {
+    let profiles = output
         .context(|| "Could not get a list of existing AWS CLI profiles.".to_owned())?
         .split_whitespace()
         .map(|s| s.to_owned())
@@ -86,3 +113,339 @@ fn get_existing_profiles(context: &Application) -> Result<Vec<String>> {
 
     Ok(profiles)
 }
+
+/// Parses the output of the command used to get the region configured for an AWS CLI profile.
+///
+/// The AWS CLI fails if the region is not configured, so an error is the same as no region.
+fn parse_region(output: Result<String>) -> Option<String> {
+    output
+        .ok()
+        .map(|region| region.trim().to_owned())
+        .filter(|region| !region.is_empty())
+}
+
+/// Resolves the name of the AWS region to use with an AWS CLI profile.
+///
+/// The region provided using the `--region` option is used first, followed by the region
+/// configured for the profile, and then the region setting.
+pub fn resolve_region(context: &Application, profile: &str) -> Option<String> {
+    context
+        .region()
+        .map(|region| region.to_owned())
+        .or_else(|| get_profile_region(context, profile))
+        .or_else(|| context.settings().region().map(|region| region.to_owned()))
+}
+
+/// Resolves the name of the AWS CLI profile to use, creating it from a template if necessary.
+///
+/// If a profile was not provided using the `--profile` option, the user is prompted to select one
+/// from the existing AWS CLI profiles and the available profile templates. If the profile does not
+/// exist but has a corresponding template, the AWS CLI profile is created using the template.
+pub fn resolve_profile(context: &Application) -> Result<String> {
+    let existing = get_existing_profiles(context)?;
+
+    select_profile(context, &existing)
+}
+
+/// Resolves the name of the AWS CLI profile to use, and the name of the AWS region to use with it.
+///
+/// The profile is resolved the same way as [`resolve_profile`], and the region the same way as
+/// [`resolve_region`]. If the profile was provided using the `--profile` option, its region is
+/// looked up at the same time as the existing profiles are listed, since neither depends on the
+/// other.
+pub fn resolve_profile_and_region(context: &Application) -> Result<(String, Option<String>)> {
+    let (existing, looked_up) = match (context.profile(), context.region()) {
+        (Some(profile), None) => {
+            let [existing, region] = Run::output_all(
+                context,
+                [list_profiles(context), get_region(context, profile)],
+            );
+
+            (parse_profiles(existing)?, Some(parse_region(region)))
+        }
+        _ => (get_existing_profiles(context)?, None),
+    };
+
+    let profile = select_profile(context, &existing)?;
+
+    // The region is looked up again if the profile did not exist until it was just created.
+    let region = match looked_up {
+        Some(region) if existing.contains(&profile) => {
+            region.or_else(|| context.settings().region().map(|region| region.to_owned()))
+        }
+        _ => resolve_region(context, &profile),
+    };
+
+    Ok((profile, region))
+}
+
+/// Selects the AWS CLI profile to use from the existing profiles and the profile templates.
+///
+/// If the selected profile does not exist but has a corresponding template, the AWS CLI profile is
+/// created using the template.
+fn select_profile(context: &Application, existing: &[String]) -> Result<String> {
+    let profiles = profile::get_profiles()?;
+    let profile = match context.profile() {
+        Some(profile) => profile.to_owned(),
+        None => {
+            let mut merged = profiles
+                .keys()
+                .map(|s| s.as_str())
+                .chain(existing.iter().map(|s| s.as_str()))
+                .collect::<Vec<&str>>();
+
+            merged.sort_unstable();
+            merged.dedup();
+
+            if merged.is_empty() {
+                err!(1, "There are no profiles available to choose from.");
+            }
+
+            select(
+                "Please select a profile to use:",
+                &merged,
+                context.settings().auto_select(),
+            )?
+            .to_string()
+        }
+    };
+
+    if !existing.iter().any(|p| p == profile.as_str()) {
+        if let Some(profile) = profiles.get(&profile) {
+            create_profile(context, profile)?;
+        } else {
+            err!(1, "The profile, {}, does not exist.", profile);
+        }
+    }
+
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::settings::Settings;
+    use crate::util::run::Script;
+    use crate::util::shell::test::ENV_LOCK;
+    use std::env;
+    use std::io::Seek;
+
+    /// The arguments used to list the existing profiles.
+    const LIST_ARGS: &[&str] = &["configure", "list-profiles"];
+
+    #[test]
+    fn profile_not_found() {
+        let context = Application::test(Some("missing".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output("default\nprod\n"),
+        );
+
+        let result = resolve_profile(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "The profile, missing, does not exist.\n"
+        );
+    }
+
+    #[test]
+    fn profile_resolved() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output("default\ndev\nprod\n"),
+        );
+
+        assert_eq!(resolve_profile(&context).unwrap(), "dev");
+    }
+
+    #[test]
+    fn profiles_not_listed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .error("Unable to parse config file: ~/.aws/config")
+                .status(255),
+        );
+
+        let result = resolve_profile(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Could not get a list of existing AWS CLI profiles.\n  Unable to parse config file: ~/.aws/config\n"
+        );
+    }
+
+    #[test]
+    fn region_of_profile() {
+        let context = Application::test(None, None).with_executor(
+            Script::default()
+                .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                .output("us-west-2\n")
+                .expect("aws", &["--profile", "prod", "configure", "get", "region"])
+                .status(1),
+        );
+
+        assert_eq!(
+            get_profile_region(&context, "dev").as_deref(),
+            Some("us-west-2")
+        );
+        assert_eq!(get_profile_region(&context, "prod"), None);
+    }
+
+    #[test]
+    fn profile_and_region_resolved() {
+        let mut settings = Settings::default();
+
+        settings.set("region", &["eu-west-1".to_owned()]).unwrap();
+
+        let context = Application::test(Some("dev".to_owned()), None)
+            .with_settings(settings)
+            .with_executor(
+                Script::default()
+                    .expect("aws", LIST_ARGS)
+                    .output("default\ndev\n")
+                    .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                    .status(1),
+            );
+
+        assert_eq!(
+            resolve_profile_and_region(&context).unwrap(),
+            ("dev".to_owned(), Some("eu-west-1".to_owned()))
+        );
+
+        let context = Application::test(Some("dev".to_owned()), Some("us-east-1".to_owned()))
+            .with_executor(
+                Script::default()
+                    .expect("aws", LIST_ARGS)
+                    .output("default\ndev\n"),
+            );
+
+        assert_eq!(
+            resolve_profile_and_region(&context).unwrap(),
+            ("dev".to_owned(), Some("us-east-1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn region_resolved() {
+        let mut settings = Settings::default();
+
+        settings.set("region", &["eu-west-1".to_owned()]).unwrap();
+
+        let context = Application::test(None, None)
+            .with_settings(settings)
+            .with_executor(
+                Script::default()
+                    .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                    .output("us-west-2\n")
+                    .expect("aws", &["--profile", "prod", "configure", "get", "region"])
+                    .status(1),
+            );
+
+        assert_eq!(
+            resolve_region(&context, "dev").as_deref(),
+            Some("us-west-2")
+        );
+        assert_eq!(
+            resolve_region(&context, "prod").as_deref(),
+            Some("eu-west-1")
+        );
+
+        let context = Application::test(None, Some("us-east-1".to_owned()));
+
+        assert_eq!(
+            resolve_region(&context, "dev").as_deref(),
+            Some("us-east-1")
+        );
+    }
+
+    #[test]
+    fn profile_printed() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+
+        env::remove_var("AWS_LOGIN_VARS");
+
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", LIST_ARGS)
+                .output("default\ndev\nprod\n"),
+        );
+
+        let result = Subcommand {
+            env: EnvOptions {
+                print: true,
+                shell: Some("bash".to_owned()),
+            },
+        }
+        .execute(&context);
+
+        env::remove_var("AWS_LOGIN_VARS");
+
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output.to_string_lossy(),
+            "export AWS_PROFILE='dev'\nexport AWS_LOGIN_VARS='AWS_PROFILE'\n"
+        );
+    }
+
+    #[test]
+    fn profile_created() {
+        let context =
+            Application::test(None, None)
+                .with_verbose(1)
+                .with_executor(Script::default().expect(
+                    "aws",
+                    &[
+                        "--profile",
+                        "dev",
+                        "configure",
+                        "set",
+                        "aws_secret_access_key",
+                        "secret",
+                    ],
+                ));
+
+        let profile = profile::Profile::test("dev", &[("aws_secret_access_key", "secret")]);
+        let result = create_profile(&context, &profile);
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert!(error.to_string_lossy().starts_with(
+            "aws-login: aws --profile dev configure set aws_secret_access_key '<redacted>' (exit status 0, "
+        ));
+    }
+
+    #[test]
+    fn profile_not_created() {
+        let context = Application::test(None, None).with_executor(
+            Script::default()
+                .expect(
+                    "aws",
+                    &[
+                        "--profile",
+                        "dev",
+                        "configure",
+                        "set",
+                        "region",
+                        "us-east-1",
+                    ],
+                )
+                .error("Permission denied.")
+                .status(1),
+        );
+
+        let profile = profile::Profile::test("dev", &[("region", "us-east-1")]);
+        let result = create_profile(&context, &profile);
+
+        assert!(format!("{}", result.unwrap_err())
+            .starts_with("Could not set the profile setting, region.\n"));
+    }
+}
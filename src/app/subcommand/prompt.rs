@@ -0,0 +1,231 @@
+//! A subcommand used to print a segment for the shell prompt.
+
+use crate::app::Application;
+use crate::util::aws::{get_profile_settings, get_session_expiry};
+use carli::prelude::cmd::*;
+use crossterm::style::{Color, ResetColor, SetForegroundColor};
+use std::io::Write;
+use std::{env, time};
+
+/// The options for the subcommand.
+#[derive(clap::Parser)]
+pub struct Subcommand {
+    /// The color of the segment (e.g. dark_yellow).
+    ///
+    /// The supported colors are: black, blue, cyan, dark_blue, dark_cyan, dark_green, dark_grey,
+    /// dark_magenta, dark_red, dark_yellow, green, grey, magenta, red, white, yellow
+    #[clap(long)]
+    color: Option<String>,
+
+    /// The color of the segment when the SSO session has expired (e.g. red).
+    #[clap(long)]
+    expired_color: Option<String>,
+
+    /// The format of the segment.
+    ///
+    /// The placeholders {profile}, {region}, and {expiry} are replaced with the name of the active
+    /// AWS CLI profile, the region, and the time left before the SSO session expires. Any text
+    /// surrounded by [ and ] is left out if a placeholder directly inside it has no value.
+    #[clap(short, long, default_value = "{profile}[@{region}][ ({expiry})]")]
+    format: String,
+
+    /// The shell the segment is printed for (e.g. bash).
+    ///
+    /// Some shells need to be told which parts of the prompt do not take up any space, otherwise
+    /// colors will cause the cursor to be misplaced. This is needed for bash and zsh.
+    #[clap(short, long)]
+    shell: Option<String>,
+}
+
+impl Subcommand {
+    /// Returns the parsed color for the segment, if one was provided.
+    fn get_color(&self, expired: bool) -> Result<Option<Color>> {
+        let name = if expired {
+            self.expired_color.as_ref().or(self.color.as_ref())
+        } else {
+            self.color.as_ref()
+        };
+
+        match name {
+            Some(name) => match Color::try_from(name.as_str()) {
+                Ok(color) => Ok(Some(color)),
+                Err(_) => err!(1, "The color, {}, is not supported.", name),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+impl Execute<Application> for Subcommand {
+    fn execute(&self, context: &Application) -> Result<()> {
+        let profile = match context
+            .profile()
+            .map(|profile| profile.to_owned())
+            .or_else(|| env::var("AWS_PROFILE").ok())
+            .or_else(|| env::var("AWS_DEFAULT_PROFILE").ok())
+        {
+            Some(profile) => profile,
+            None => return Ok(()),
+        };
+
+        let region = context
+            .region()
+            .map(|region| region.to_owned())
+            .or_else(|| env::var("AWS_REGION").ok())
+            .or_else(|| env::var("AWS_DEFAULT_REGION").ok())
+            .or_else(|| get_profile_settings(&profile).remove("region"))
+            .or_else(|| context.settings().region().map(|region| region.to_owned()))
+            .unwrap_or_default();
+
+        let remaining = get_session_expiry(&profile).map(|expiry| {
+            expiry
+                .duration_since(time::SystemTime::now())
+                .unwrap_or_default()
+        });
+
+        let expired = remaining.map_or(false, |remaining| remaining.as_secs() < 60);
+        let expiry = match remaining {
+            Some(remaining) => format_remaining(remaining),
+            None => String::new(),
+        };
+
+        let segment = render(
+            &self.format,
+            &[
+                ("expiry", expiry.as_str()),
+                ("profile", profile.as_str()),
+                ("region", region.as_str()),
+            ],
+        );
+
+        let (start, end) = match self.shell.as_deref() {
+            Some("bash") => ("\x01", "\x02"),
+            Some("zsh") => ("%{", "%}"),
+            _ => ("", ""),
+        };
+
+        let mut output = context.output();
+
+        match self.get_color(expired)? {
+            Some(color) => write!(
+                output,
+                "{}{}{}{}{}{}{}",
+                start,
+                SetForegroundColor(color),
+                end,
+                segment,
+                start,
+                ResetColor,
+                end
+            )?,
+            None => write!(output, "{}", segment)?,
+        }
+
+        output.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Formats the time left before a session expires (e.g. 1h42m).
+fn format_remaining(remaining: time::Duration) -> String {
+    let minutes = remaining.as_secs() / 60;
+
+    if minutes == 0 {
+        "expired".to_owned()
+    } else if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h{}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Renders the format of the segment using the given placeholder values.
+///
+/// Optional groups, surrounded by `[` and `]`, may be nested. A group is left out if any of the
+/// placeholders directly inside of it has an empty value.
+fn render(format: &str, values: &[(&str, &str)]) -> String {
+    let mut chars = format.chars();
+
+    render_group(&mut chars, values).unwrap_or_default()
+}
+
+/// Renders a group of the format until the end of the group, or the format, is reached.
+///
+/// If a placeholder in the group has an empty value, [`None`] is returned.
+fn render_group(chars: &mut std::str::Chars, values: &[(&str, &str)]) -> Option<String> {
+    let mut rendered = String::new();
+    let mut complete = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                if let Some(group) = render_group(chars, values) {
+                    rendered.push_str(&group);
+                }
+            }
+            ']' => break,
+            '{' => {
+                let name = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+
+                match values.iter().find(|(n, _)| *n == name) {
+                    Some((_, value)) => {
+                        if value.is_empty() {
+                            complete = false;
+                        }
+
+                        rendered.push_str(value);
+                    }
+                    None => {
+                        rendered.push('{');
+                        rendered.push_str(&name);
+                        rendered.push('}');
+                    }
+                }
+            }
+            _ => rendered.push(c),
+        }
+    }
+
+    if complete {
+        Some(rendered)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remaining_formatting() {
+        assert_eq!(format_remaining(time::Duration::from_secs(30)), "expired");
+        assert_eq!(format_remaining(time::Duration::from_secs(42 * 60)), "42m");
+        assert_eq!(
+            format_remaining(time::Duration::from_secs(102 * 60)),
+            "1h42m"
+        );
+    }
+
+    #[test]
+    fn segment_rendering() {
+        let format = "[{profile}[@{region}][ ({expiry})] ]";
+        let values = [
+            ("expiry", "42m"),
+            ("profile", "dev-write"),
+            ("region", "us-east-1"),
+        ];
+
+        assert_eq!(render(format, &values), "dev-write@us-east-1 (42m) ");
+        assert_eq!(
+            render(
+                format,
+                &[("expiry", ""), ("profile", "dev"), ("region", "")]
+            ),
+            "dev "
+        );
+        assert_eq!(render(format, &[("profile", "")]), "");
+        assert_eq!(render("{unknown}", &values), "{unknown}");
+    }
+}
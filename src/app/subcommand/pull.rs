@@ -60,27 +60,32 @@ pub struct Subcommand {
     resolve: Option<Resolve>,
 
     /// The URL to download the profile templates from.
-    url: String,
+    ///
+    /// If a URL is not provided, the profile templates are downloaded from each of the URLs in the
+    /// template_urls setting, with the templates from later URLs replacing earlier ones of the same
+    /// name.
+    url: Option<String>,
 }
 
 impl Execute<Application> for Subcommand {
-    fn execute(&self, _: &Application) -> Result<()> {
-        let json = match reqwest::blocking::get(&self.url) {
-            Ok(response) => match response.text() {
-                Ok(text) => text,
-                Err(error) => {
-                    return Err(error!(1, "{}", error)
-                        .context("The download response could not be read.".to_string()))
-                }
-            },
-            Err(error) => {
-                return Err(error!(1, "{}", error)
-                    .context("The templates could not be downloaded.".to_string()))
-            }
+    fn execute(&self, context: &Application) -> Result<()> {
+        let urls = match &self.url {
+            Some(url) => vec![url.to_owned()],
+            None => context.settings().template_urls().to_vec(),
         };
 
-        let remote = profile::parse_templates(json.as_bytes())
-            .context(|| "Could not parse the downloaded templates.".to_owned())?;
+        if urls.is_empty() {
+            err!(
+                1,
+                "A URL is required if the template_urls setting is not set."
+            );
+        }
+
+        let mut remote = profile::Templates::new();
+
+        for url in &urls {
+            remote.extend(download(url)?);
+        }
 
         let mut templates = profile::get_templates()?;
 
@@ -94,7 +99,7 @@ impl Execute<Application> for Subcommand {
                     let prompt = "What would you like to do with the existing templates?";
                     let choices = &[Resolve::Cancel, Resolve::Merge, Resolve::Replace];
 
-                    select(prompt, choices)?
+                    select(prompt, choices, false)?
                 }
             };
 
@@ -118,3 +123,23 @@ impl Execute<Application> for Subcommand {
         Ok(())
     }
 }
+
+/// Downloads and parses the profile templates from a URL.
+fn download(url: &str) -> Result<profile::Templates> {
+    let json = match reqwest::blocking::get(url) {
+        Ok(response) => match response.text() {
+            Ok(text) => text,
+            Err(error) => {
+                return Err(error!(1, "{}", error)
+                    .context("The download response could not be read.".to_string()))
+            }
+        },
+        Err(error) => {
+            return Err(error!(1, "{}", error)
+                .context("The templates could not be downloaded.".to_string()))
+        }
+    };
+
+    profile::parse_templates(json.as_bytes())
+        .context(|| "Could not parse the downloaded templates.".to_owned())
+}
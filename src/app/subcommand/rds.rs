@@ -7,19 +7,36 @@ use carli::errorln;
 use carli::prelude::cmd::*;
 use std::fmt;
 
-/// Represents an RDS Proxy that is available.
+/// The output of the AWS CLI when describing the proxies.
+#[derive(serde::Deserialize)]
+struct Proxies {
+    /// The proxies that were described.
+    #[serde(rename = "DBProxies")]
+    proxies: Vec<Proxy>,
+}
+
+/// Represents an RDS Proxy.
+#[derive(serde::Deserialize)]
 struct Proxy {
     /// The host name for the endpoint of the proxy.
+    #[serde(default, rename = "Endpoint")]
     endpoint: String,
 
     /// The database engine family.
+    #[serde(rename = "EngineFamily")]
     engine: String,
 
     /// The name of the proxy.
+    #[serde(rename = "DBProxyName")]
     name: String,
 
     /// The flag used to indicate if TLS is required.
+    #[serde(default, rename = "RequireTLS")]
     require_tls: bool,
+
+    /// The status of the proxy (e.g. available).
+    #[serde(rename = "Status")]
+    status: String,
 }
 
 impl fmt::Display for Proxy {
@@ -32,6 +49,9 @@ impl fmt::Display for Proxy {
 #[derive(clap::Parser)]
 pub struct Subcommand {
     /// The database port number.
+    ///
+    /// If a port number is not provided, the rds_port setting is used. If neither are available,
+    /// the port number for PostgreSQL (5432) is used.
     #[clap(short, long)]
     port: Option<String>,
 
@@ -42,9 +62,23 @@ pub struct Subcommand {
 impl Execute<Application> for Subcommand {
     fn execute(&self, context: &Application) -> Result<()> {
         let proxies = get_proxies(context)?;
-        let proxy = select("Please select an RDS Proxy:", &proxies)?;
 
-        if proxy.engine != "POSTGRESQL" && self.port.is_none() {
+        if proxies.is_empty() {
+            err!(1, "No available RDS proxies were found.");
+        }
+
+        let proxy = select(
+            "Please select an RDS Proxy:",
+            &proxies,
+            context.settings().auto_select(),
+        )?;
+
+        let port = match &self.port {
+            Some(port) => Some(port.to_owned()),
+            None => context.settings().rds_port().map(|port| port.to_string()),
+        };
+
+        if proxy.engine != "POSTGRESQL" && port.is_none() {
             err!(
                 1,
                 "The database server port number is required for {} engines.",
@@ -59,14 +93,14 @@ impl Execute<Application> for Subcommand {
             )?;
         }
 
-        Run::new("aws")
+        Run::aws(context)
             .with_aws_options(context)
             .arg("rds")
             .arg("generate-db-auth-token")
             .arg("--hostname")
             .arg(&proxy.endpoint)
             .arg("--port")
-            .arg(self.port.as_deref().unwrap_or("5432"))
+            .arg(port.as_deref().unwrap_or("5432"))
             .arg("--username")
             .arg(&self.username)
             .pass_through(context)?;
@@ -77,51 +111,268 @@ impl Execute<Application> for Subcommand {
 
 /// Retrieves a list of the available RDS Proxies.
 fn get_proxies(context: &Application) -> Result<Vec<Proxy>> {
-    let pairs = Run::new("aws")
+    let proxies = Run::aws(context)
         .with_aws_options(context)
         .arg("rds")
         .arg("describe-db-proxies")
-        .arg("--query")
-        .arg("DBProxies[].[DBProxyName,Endpoint,EngineFamily,RequireTLS, Status]")
-        .arg("--output")
-        .arg("text")
-        .output()
-        .map(|output| output.trim().to_owned())
+        .read_only()
+        .json::<Proxies>(context)
         .context(|| "Could not get RDS Proxy host names from AWS CLI.".to_owned())?
-        .split('\n')
-        .map(|s| s.to_owned())
-        .collect::<Vec<String>>();
-
-    let mut host_names = Vec::new();
-
-    for pair in pairs {
-        let mut parts = pair
-            .split('\t')
-            .map(|s| s.to_owned())
-            .collect::<Vec<String>>();
-
-        let (status, require_tls, engine, endpoint, name) = (
-            parts.remove(4),
-            parts.remove(3),
-            parts.remove(2),
-            parts.remove(1),
-            parts.remove(0),
-        );
+        .proxies
+        .into_iter()
+        .filter(|proxy| proxy.status == "available")
+        .collect();
+
+    Ok(proxies)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::settings::Settings;
+    use crate::util::run::Script;
+    use std::io::Seek;
+
+    /// The arguments used to describe the available proxies.
+    const DESCRIBE_ARGS: &[&str] = &[
+        "--profile",
+        "dev",
+        "rds",
+        "describe-db-proxies",
+        "--output",
+        "json",
+    ];
+
+    /// The proxies described by the AWS CLI.
+    const PROXIES: &str = r#"{
+        "DBProxies": [
+            {
+                "DBProxyName": "app",
+                "Endpoint": "app.proxy.rds.amazonaws.com",
+                "EngineFamily": "POSTGRESQL",
+                "RequireTLS": true,
+                "Status": "available"
+            },
+            {
+                "DBProxyName": "legacy",
+                "Endpoint": "legacy.proxy.rds.amazonaws.com",
+                "EngineFamily": "MYSQL",
+                "RequireTLS": false,
+                "Status": "available"
+            },
+            {
+                "DBProxyName": "new",
+                "EngineFamily": "POSTGRESQL",
+                "Status": "creating"
+            }
+        ]
+    }"#;
+
+    /// The only available proxy described by the AWS CLI, which uses PostgreSQL.
+    const POSTGRESQL_PROXY: &str = r#"{
+        "DBProxies": [
+            {
+                "DBProxyName": "app",
+                "Endpoint": "app.proxy.rds.amazonaws.com",
+                "EngineFamily": "POSTGRESQL",
+                "RequireTLS": true,
+                "Status": "available"
+            }
+        ]
+    }"#;
+
+    /// The only available proxy described by the AWS CLI, which uses MySQL.
+    const MYSQL_PROXY: &str = r#"{
+        "DBProxies": [
+            {
+                "DBProxyName": "legacy",
+                "Endpoint": "legacy.proxy.rds.amazonaws.com",
+                "EngineFamily": "MYSQL",
+                "RequireTLS": false,
+                "Status": "available"
+            }
+        ]
+    }"#;
+
+    /// Creates a test instance of the application that selects the only available proxy.
+    fn context(script: Script) -> Application {
+        let mut settings = Settings::default();
 
-        if status == "available" {
-            let proxy = Proxy {
-                require_tls: require_tls
-                    .to_lowercase()
-                    .parse::<bool>()
-                    .expect("The RequireTLS field from the AWS CLI is not a boolean value."),
-                endpoint,
-                engine,
-                name,
-            };
-
-            host_names.push(proxy);
+        settings.set("auto_select", &["true".to_owned()]).unwrap();
+
+        Application::test(Some("dev".to_owned()), None)
+            .with_executor(script)
+            .with_settings(settings)
+    }
+
+    /// Creates the options for the subcommand.
+    fn subcommand(port: Option<&str>) -> Subcommand {
+        Subcommand {
+            port: port.map(|port| port.to_owned()),
+            username: "admin".to_owned(),
         }
     }
 
-    Ok(host_names)
+    #[test]
+    fn port_required() {
+        let context = context(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(MYSQL_PROXY),
+        );
+
+        let result = subcommand(None).execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "The database server port number is required for MYSQL engines.\n"
+        );
+    }
+
+    #[test]
+    fn proxies_described() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(PROXIES),
+        );
+
+        let proxies = get_proxies(&context).unwrap();
+
+        assert_eq!(proxies.len(), 2);
+        assert_eq!(proxies[0].name, "app");
+        assert_eq!(proxies[0].endpoint, "app.proxy.rds.amazonaws.com");
+        assert!(proxies[0].require_tls);
+        assert_eq!(proxies[1].engine, "MYSQL");
+        assert!(!proxies[1].require_tls);
+    }
+
+    #[test]
+    fn proxies_described_without_proxies() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(r#"{"DBProxies": []}"#),
+        );
+
+        assert!(get_proxies(&context).unwrap().is_empty());
+    }
+
+    #[test]
+    fn proxies_described_with_unexpected_output() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(r#"{"DBProxies": [{"DBProxyName": "app"}]}"#),
+        );
+
+        let result = get_proxies(&context);
+
+        assert!(format!("{}", result.err().unwrap()).starts_with(
+            "Could not get RDS Proxy host names from AWS CLI.\n  The output of the command is not in the expected format: "
+        ));
+    }
+
+    #[test]
+    fn proxies_not_described() {
+        let context = context(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .error("Unable to locate credentials.")
+                .status(255),
+        );
+
+        let result = subcommand(None).execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Could not get RDS Proxy host names from AWS CLI.\n  Unable to locate credentials.\n"
+        );
+    }
+
+    #[test]
+    fn proxies_not_found() {
+        let context = context(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(r#"{"DBProxies": [{"DBProxyName": "new", "EngineFamily": "POSTGRESQL", "Status": "creating"}]}"#),
+        );
+
+        let result = subcommand(None).execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "No available RDS proxies were found.\n"
+        );
+    }
+
+    #[test]
+    fn token_generated() {
+        let context = context(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(POSTGRESQL_PROXY)
+                .expect(
+                    "aws",
+                    &[
+                        "--profile",
+                        "dev",
+                        "rds",
+                        "generate-db-auth-token",
+                        "--hostname",
+                        "app.proxy.rds.amazonaws.com",
+                        "--port",
+                        "5432",
+                        "--username",
+                        "admin",
+                    ],
+                )
+                .output("token\n"),
+        );
+
+        let result = subcommand(None).execute(&context);
+
+        let mut error = context.error();
+        let mut output = context.output();
+
+        error.rewind().unwrap();
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            error.to_string_lossy(),
+            "Warning: This connection requires TLS to be used.\n\n"
+        );
+        assert_eq!(output.to_string_lossy(), "token\n");
+    }
+
+    #[test]
+    fn token_not_generated() {
+        let context = context(
+            Script::default()
+                .expect("aws", DESCRIBE_ARGS)
+                .output(MYSQL_PROXY)
+                .expect(
+                    "aws",
+                    &[
+                        "--profile",
+                        "dev",
+                        "rds",
+                        "generate-db-auth-token",
+                        "--hostname",
+                        "legacy.proxy.rds.amazonaws.com",
+                        "--port",
+                        "3306",
+                        "--username",
+                        "admin",
+                    ],
+                )
+                .error("Could not connect to the endpoint URL.")
+                .status(255),
+        );
+
+        let result = subcommand(Some("3306")).execute(&context);
+
+        assert!(format!("{:?}", result.unwrap_err()).contains("status: 255"));
+    }
 }
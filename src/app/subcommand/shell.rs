@@ -1,12 +1,24 @@
 //! A subcommand used to used to integrate the application with the user's shell.
 
+use super::profile::resolve_profile_and_region;
 use crate::app::Application;
-use crate::util::shell::get_setup;
-use carli::outputln;
+use crate::util::config::BIN_NAME;
+use crate::util::run::Run;
+use crate::util::shell::{
+    detect_shell, get_setup, Setup, SCRIPT_PATH, SHELLS, SHELL_NAME, SUBSHELL_PROFILE,
+};
 use carli::prelude::cmd::*;
 use carli::{error, error::Error};
+use carli::{errorln, outputln};
+use std::env;
 use std::str::FromStr;
 
+/// The name of the environment variable used to record the profiles of the nested shells.
+///
+/// The profiles of the shell started by the spawn action, and of every shell it is nested in, are
+/// recorded as a JSON encoded array so that a shell is not started again for any of them.
+const NESTED_VAR: &str = "AWS_LOGIN_SUBSHELLS";
+
 /// The actions supported by the subcommand.
 enum Action {
     /// Generate the startup script initialization shell code.
@@ -14,6 +26,18 @@ enum Action {
 
     /// Modify the shell profile to inject our initialization shell code.
     Install,
+
+    /// Generate the shell code that adds the prompt segment to the shell prompt.
+    Prompt,
+
+    /// Start a new shell using an AWS CLI profile.
+    Spawn,
+
+    /// Report on the health of the integration for each supported shell.
+    Status,
+
+    /// Modify the shell profile to remove our initialization shell code.
+    Uninstall,
 }
 
 impl FromStr for Action {
@@ -23,55 +47,330 @@ impl FromStr for Action {
         match s {
             "init" => Ok(Self::Init),
             "install" => Ok(Self::Install),
+            "prompt" => Ok(Self::Prompt),
+            "spawn" => Ok(Self::Spawn),
+            "status" => Ok(Self::Status),
+            "uninstall" => Ok(Self::Uninstall),
             _ => Err(s.to_owned()),
         }
     }
 }
 
+/// The state of the integration in the environment of the current process.
+#[derive(serde::Serialize)]
+struct EnvironmentStatus {
+    /// The value of the environment variable used to specify the shell script path.
+    script: Option<String>,
+
+    /// The value of the environment variable used to specify the shell name.
+    shell: Option<String>,
+}
+
+/// The state of the integration in the startup script of a shell.
+#[derive(serde::Serialize)]
+struct ShellStatus {
+    /// The flag used to indicate if the installed integration is up to date.
+    current: bool,
+
+    /// The flag used to indicate if the integration is installed.
+    installed: bool,
+
+    /// The name of the shell.
+    name: String,
+
+    /// The path to the profile's startup script.
+    script: String,
+}
+
+/// The report generated by the status action.
+#[derive(serde::Serialize)]
+struct Status {
+    /// The absolute path to the application binary.
+    binary: String,
+
+    /// The state of the integration in the current process.
+    environment: EnvironmentStatus,
+
+    /// The state of the integration in each shell.
+    shells: Vec<ShellStatus>,
+}
+
 /// The options for the subcommand.
 #[derive(clap::Parser)]
 pub struct Subcommand {
     /// What the subcommand should do with the shell environment.
     ///
-    /// The subcommand is capable of a couple of actions: install, init. The install action will
-    /// modify the shell profile's startup script to integrate this application. The init action
-    /// will generate the initialization shell code for the integration.
+    /// The subcommand is capable of a few actions: install, init, prompt, spawn, status,
+    /// uninstall. The install action will modify the shell profile's startup script to integrate
+    /// this application. The init action will generate the initialization shell code for the
+    /// integration. The prompt action will generate shell code that adds the segment printed by
+    /// the prompt subcommand to the shell prompt. The spawn action will start a new shell ($SHELL)
+    /// using the AWS CLI profile, returning to the current shell once it exits. The status action
+    /// will report whether the integration is installed, up to date, and active. The uninstall
+    /// action will remove the integration from the startup script, keeping a backup.
     action: Action,
 
     /// The path to the shell profile's startup script (e.g. ~/.bashrc).
     #[clap(short, long)]
     init: Option<String>,
 
+    /// Prints the report generated by the status action as JSON.
+    #[clap(long)]
+    json: bool,
+
     /// The name of the shell used to manage the environment (e.g. bash).
     ///
     /// The subcommand needs to know what shell environment it will be modifying in order to
-    /// provide shell specific support for the integration (e.g. Bash vs PowerShell). Please
-    /// open a ticket to request support for additional shells.
+    /// provide shell specific support for the integration (e.g. Bash vs PowerShell). If a shell
+    /// is not provided, the shell setting is used, or it will be detected using the parent process
+    /// or the SHELL environment variable. Please open a ticket to request support for additional
+    /// shells.
     ///
-    /// The supported shells are: bash, fish, powershell, zsh
+    /// The supported shells are: bash, elvish, fish, nu, powershell, tcsh, xonsh, zsh
     #[clap(short, long)]
-    shell: String,
+    shell: Option<String>,
+}
+
+impl Subcommand {
+    /// Returns the [`Setup`] implementation for the provided, preferred, or detected shell.
+    fn setup(&self, context: &Application) -> Result<Box<dyn Setup>> {
+        let shell = match self.shell.as_deref().or_else(|| context.settings().shell()) {
+            Some(shell) => shell,
+            None => detect_shell().ok_or_else(|| {
+                error!(
+                    1,
+                    "The shell could not be detected, please use --shell with one of: {}",
+                    SHELLS.join(", ")
+                )
+            })?,
+        };
+
+        get_setup(shell, self.init.as_deref()).ok_or_else(|| {
+            error!(
+                1,
+                "The shell, {}, is not supported. The supported shells are: {}",
+                shell,
+                SHELLS.join(", ")
+            )
+        })
+    }
+
+    /// Starts a new shell with the environment prepared for an AWS CLI profile.
+    ///
+    /// The profile is resolved the same way the use subcommand does. The new shell is marked with
+    /// [`SUBSHELL_PROFILE`] for prompts, and records the profiles of the shells it is nested in so
+    /// that a subshell for any of those profiles is not started within it.
+    fn spawn(&self, context: &Application) -> Result<()> {
+        let (profile, region) = resolve_profile_and_region(context)?;
+        let mut nested = get_nested();
+
+        if nested.contains(&profile) {
+            err!(
+                1,
+                "A shell was already started for the profile, {}. Exit it, and any shell started in it, to return to the previous shell.",
+                profile
+            );
+        }
+
+        nested.push(profile.clone());
+
+        let program = match env::var("SHELL") {
+            Ok(program) if !program.is_empty() => program,
+            _ if cfg!(windows) => "powershell".to_owned(),
+            _ => "sh".to_owned(),
+        };
+
+        let mut run = Run::new(&program);
+
+        run.env("AWS_PROFILE", &profile)
+            .env(SUBSHELL_PROFILE, &profile)
+            .env(
+                NESTED_VAR,
+                &serde_json::to_string(&nested).unwrap_or_default(),
+            )
+            .env_remove(SCRIPT_PATH)
+            .env_remove(SHELL_NAME);
+
+        if let Some(region) = region {
+            run.env("AWS_REGION", &region);
+        }
+
+        errorln!(
+            context,
+            "Starting a new shell for the profile, {}. Exit the shell to return.",
+            profile
+        )?;
+
+        run.interactive(context)
+    }
+
+    /// Generates and prints a report on the health of the integration.
+    ///
+    /// If a shell was provided, only that shell is checked. Otherwise, the default startup script
+    /// for every supported shell is checked. A startup script provided using `--init` can only be
+    /// checked for the shell it was provided for.
+    fn status(&self, context: &Application) -> Result<()> {
+        if self.init.is_some() && self.shell.is_none() {
+            err!(
+                1,
+                "Each shell uses a different startup script, so --init requires --shell with the status action."
+            );
+        }
+
+        let names = match self.shell.as_deref() {
+            Some(shell) => vec![shell],
+            None => SHELLS.to_vec(),
+        };
+
+        let mut shells = Vec::new();
+
+        for name in names {
+            let setup = get_setup(name, self.init.as_deref())
+                .ok_or_else(|| error!(1, "The shell, {}, is not supported.", name))?;
+
+            let installed = setup.is_installed().context(|| {
+                format!("Could not check if the integration is set up for {}.", name)
+            })?;
+
+            let current = installed
+                && setup.is_current().context(|| {
+                    format!(
+                        "Could not check if the integration is up to date for {}.",
+                        name
+                    )
+                })?;
+
+            shells.push(ShellStatus {
+                current,
+                installed,
+                name: name.to_owned(),
+                script: setup.script().display().to_string(),
+            });
+        }
+
+        let status = Status {
+            binary: BIN_NAME.to_owned(),
+            environment: EnvironmentStatus {
+                script: env::var(SCRIPT_PATH).ok(),
+                shell: env::var(SHELL_NAME).ok(),
+            },
+            shells,
+        };
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&status)
+                .map_err(|error| error!(1, "{}", error))
+                .context(|| "Could not generate the JSON report.".to_owned())?;
+
+            outputln!(context, "{}", json)?;
+
+            return Ok(());
+        }
+
+        outputln!(context, "Binary: {}\n", status.binary)?;
+        outputln!(context, "Environment:")?;
+
+        for (name, value) in [
+            (SHELL_NAME, &status.environment.shell),
+            (SCRIPT_PATH, &status.environment.script),
+        ] {
+            outputln!(
+                context,
+                "  {}: {}",
+                name,
+                value.as_deref().unwrap_or("(not set)")
+            )?;
+        }
+
+        outputln!(context, "\nStartup scripts:")?;
+
+        for shell in &status.shells {
+            let state = match (shell.installed, shell.current) {
+                (true, true) => "installed",
+                (true, false) => "outdated (run `shell install` to update)",
+                _ => "not installed",
+            };
+
+            outputln!(context, "  {:<10} {}", shell.name, state)?;
+            outputln!(context, "  {:<10} {}", "", shell.script)?;
+        }
+
+        if status.environment.shell.is_none() || status.environment.script.is_none() {
+            outputln!(
+                context,
+                "\nThe integration is not active in this shell session. If it is installed, start a new session."
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the profiles of the shells started by the spawn action that this shell is nested in.
+fn get_nested() -> Vec<String> {
+    env::var(NESTED_VAR)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
 }
 
 impl Execute<Application> for Subcommand {
     fn execute(&self, context: &Application) -> Result<()> {
-        let env = get_setup(&self.shell, self.init.as_deref())
-            .ok_or_else(|| error!(1, "The shell is not supported."))?;
-
         match &self.action {
-            Action::Init => outputln!(context, "{}", env.generate_script())
+            Action::Init => outputln!(context, "{}", self.setup(context)?.generate_script())
                 .map_err(Error::from)
                 .context(|| "Could not write initialization script to output.".to_owned())?,
             Action::Install => {
+                let env = self.setup(context)?;
                 let installed = env.is_installed().context(|| {
                     "Could not check if the integration is already set up.".to_owned()
                 })?;
 
-                if installed {
+                if !installed {
+                    env.install()
+                        .context(|| "Could not install integration script.".to_owned())?
+                } else if env
+                    .is_current()
+                    .context(|| "Could not check if the integration is up to date.".to_owned())?
+                {
                     outputln!(context, "The integration is already installed.")?;
                 } else {
                     env.install()
-                        .context(|| "Could not install integration script.".to_owned())?
+                        .context(|| "Could not update integration script.".to_owned())?;
+
+                    outputln!(context, "The integration has been updated.")?;
+                }
+            }
+            Action::Prompt => {
+                let shell = self.setup(context)?;
+                let prompt = shell.generate_prompt().ok_or_else(|| {
+                    error!(1, "The prompt segment is not supported for this shell.")
+                })?;
+
+                outputln!(context, "{}", prompt)
+                    .map_err(Error::from)
+                    .context(|| "Could not write prompt script to output.".to_owned())?
+            }
+            Action::Spawn => self.spawn(context)?,
+            Action::Status => self.status(context)?,
+            Action::Uninstall => {
+                let env = self.setup(context)?;
+                let installed = env.is_installed().context(|| {
+                    "Could not check if the integration is already set up.".to_owned()
+                })?;
+
+                if installed {
+                    let backup = env
+                        .uninstall()
+                        .context(|| "Could not uninstall integration script.".to_owned())?;
+
+                    outputln!(
+                        context,
+                        "The original startup script was saved to: {}",
+                        backup.display()
+                    )?;
+                } else {
+                    outputln!(context, "The integration is not installed.")?;
                 }
             }
         }
@@ -79,3 +378,187 @@ impl Execute<Application> for Subcommand {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::run::Script;
+    use crate::util::shell::test::ENV_LOCK;
+    use std::fs;
+    use std::io::Seek;
+
+    /// The arguments used to list the existing profiles.
+    const LIST_ARGS: &[&str] = &["configure", "list-profiles"];
+
+    /// Creates the options for the spawn action.
+    fn spawn() -> Subcommand {
+        Subcommand {
+            action: Action::Spawn,
+            init: None,
+            json: false,
+            shell: None,
+        }
+    }
+
+    /// Runs the test with the environment variables used by the spawn action set.
+    fn with_env(vars: &[(&str, &str)], test: impl FnOnce()) {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+        let names = ["SHELL", NESTED_VAR, SUBSHELL_PROFILE];
+        let previous = names
+            .iter()
+            .map(|name| env::var(name).ok())
+            .collect::<Vec<Option<String>>>();
+
+        for name in names {
+            env::remove_var(name);
+        }
+
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+
+        test();
+
+        for (name, value) in names.iter().zip(previous) {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+    }
+
+    #[test]
+    fn spawn_nested_profile() {
+        with_env(
+            &[
+                ("SHELL", "/bin/sh"),
+                (NESTED_VAR, r#"["dev","prod"]"#),
+                (SUBSHELL_PROFILE, "prod"),
+            ],
+            || {
+                let context = Application::test(Some("dev".to_owned()), None).with_executor(
+                    Script::default()
+                        .expect("aws", LIST_ARGS)
+                        .output("dev\nprod\n")
+                        .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                        .output("us-west-2\n"),
+                );
+
+                assert_eq!(
+                    format!("{}", spawn().execute(&context).unwrap_err()),
+                    "A shell was already started for the profile, dev. Exit it, and any shell started in it, to return to the previous shell.\n"
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn spawn_shell() {
+        with_env(
+            &[
+                ("SHELL", "/bin/sh"),
+                (NESTED_VAR, r#"["prod"]"#),
+                (SUBSHELL_PROFILE, "prod"),
+            ],
+            || {
+                let context = Application::test(Some("dev".to_owned()), None).with_executor(
+                    Script::default()
+                        .expect("aws", LIST_ARGS)
+                        .output("dev\nprod\n")
+                        .expect("aws", &["--profile", "dev", "configure", "get", "region"])
+                        .output("us-west-2\n")
+                        .expect("/bin/sh", &[])
+                        .env("AWS_PROFILE", "dev")
+                        .env(SUBSHELL_PROFILE, "dev")
+                        .env(NESTED_VAR, r#"["prod","dev"]"#)
+                        .env("AWS_REGION", "us-west-2"),
+                );
+
+                let result = spawn().execute(&context);
+                let mut error = context.error();
+
+                error.rewind().unwrap();
+
+                assert!(result.is_ok());
+                assert_eq!(
+                    error.to_string_lossy(),
+                    "Starting a new shell for the profile, dev. Exit the shell to return.\n"
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn status_of_startup_script() {
+        let dir = env::temp_dir().join(format!("aws-login-status-{}", std::process::id()));
+        let script = dir.join(".bashrc");
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&script, "export A=1\n").unwrap();
+
+        let mut command = Subcommand {
+            action: Action::Status,
+            init: Some(script.display().to_string()),
+            json: true,
+            shell: Some("bash".to_owned()),
+        };
+
+        let status = |command: &Subcommand| {
+            let context = Application::test(None, None);
+
+            command.execute(&context).unwrap();
+
+            let mut output = context.output();
+
+            output.rewind().unwrap();
+
+            let status: serde_json::Value =
+                serde_json::from_str(&output.to_string_lossy()).unwrap();
+
+            status["shells"].clone()
+        };
+
+        assert_eq!(
+            status(&command),
+            serde_json::json!([{
+                "current": false,
+                "installed": false,
+                "name": "bash",
+                "script": script.display().to_string(),
+            }])
+        );
+
+        command.action = Action::Install;
+        command.execute(&Application::test(None, None)).unwrap();
+        command.action = Action::Status;
+
+        assert_eq!(
+            status(&command),
+            serde_json::json!([{
+                "current": true,
+                "installed": true,
+                "name": "bash",
+                "script": script.display().to_string(),
+            }])
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn status_requires_shell_for_init() {
+        let context = Application::test(None, None);
+        let result = Subcommand {
+            action: Action::Status,
+            init: Some("/tmp/.bashrc".to_owned()),
+            json: false,
+            shell: None,
+        }
+        .execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Each shell uses a different startup script, so --init requires --shell with the status action.\n"
+        );
+    }
+}
@@ -5,7 +5,7 @@ use crate::util::run;
 use carli::prelude::cmd::*;
 
 /// The profile configuration settings required for SSO.
-const REQUIRED_SETTINGS: &[&str] = &[
+const REQUIRED_SETTINGS: [&str; 4] = [
     "sso_account_id",
     "sso_region",
     "sso_role_name",
@@ -19,14 +19,14 @@ pub struct Subcommand {}
 impl Execute<Application> for Subcommand {
     fn execute(&self, context: &Application) -> Result<()> {
         if is_configured(context)? {
-            run::Run::new("aws")
+            run::Run::aws(context)
                 .with_aws_options(context)
                 .arg("sso")
                 .arg("login")
                 .pass_through(context)
                 .context(|| "Could not log in via SSO.".to_owned())?;
         } else {
-            run::Run::new("aws")
+            run::Run::aws(context)
                 .with_aws_options(context)
                 .arg("configure")
                 .arg("sso")
@@ -39,22 +39,125 @@ impl Execute<Application> for Subcommand {
 }
 
 /// Checks if the active profile is fully configured for SSO.
+///
+/// Each of the settings is read at the same time to avoid waiting for the AWS CLI to start up
+/// once for every setting.
 fn is_configured(context: &Application) -> Result<bool> {
-    let mut has = 0;
+    let runs = REQUIRED_SETTINGS.map(|key| {
+        let mut run = run::Run::aws(context);
 
-    for key in REQUIRED_SETTINGS {
-        if let Ok(value) = run::Run::new("aws")
-            .with_aws_options(context)
+        run.with_aws_options(context)
             .arg("configure")
             .arg("get")
             .arg(key)
-            .output()
-        {
-            if !value.trim().is_empty() {
-                has += 1;
+            .read_only();
+
+        run
+    });
+
+    let has = run::Run::output_all(context, runs)
+        .iter()
+        .filter(|result| matches!(result, Ok(value) if !value.trim().is_empty()))
+        .count();
+
+    Ok(has == REQUIRED_SETTINGS.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::run::Script;
+    use std::io::Seek;
+
+    /// Expects the required settings to be read, with the given settings having no value.
+    fn expect_settings(mut script: Script, missing: &[&str]) -> Script {
+        for key in REQUIRED_SETTINGS {
+            script = script.expect("aws", &["--profile", "dev", "configure", "get", key]);
+
+            if missing.contains(&key) {
+                script = script.status(1);
+            } else {
+                script = script.output("value\n");
             }
-        };
+        }
+
+        script
     }
 
-    Ok(has == REQUIRED_SETTINGS.len())
+    #[test]
+    fn configure_failed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            expect_settings(Script::default(), &REQUIRED_SETTINGS)
+                .expect("aws", &["--profile", "dev", "configure", "sso"])
+                .status(130),
+        );
+
+        let result = Subcommand {}.execute(&context);
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Could not configure AWS CLI profile for SSO.\n"
+        );
+    }
+
+    #[test]
+    fn configure_when_incomplete() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            expect_settings(Script::default(), &["sso_role_name"])
+                .expect("aws", &["--profile", "dev", "configure", "sso"])
+                .output("SSO start URL [None]: "),
+        );
+
+        let result = Subcommand {}.execute(&context);
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(output.to_string_lossy(), "SSO start URL [None]: ");
+    }
+
+    #[test]
+    fn login_failed() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            expect_settings(Script::default(), &[])
+                .expect("aws", &["--profile", "dev", "sso", "login"])
+                .error("Error when retrieving token from sso: Token has expired\n")
+                .status(255),
+        );
+
+        let result = Subcommand {}.execute(&context);
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "Could not log in via SSO.\n"
+        );
+        assert_eq!(
+            error.to_string_lossy(),
+            "Error when retrieving token from sso: Token has expired\n"
+        );
+    }
+
+    #[test]
+    fn login_when_configured() {
+        let context = Application::test(Some("dev".to_owned()), None).with_executor(
+            expect_settings(Script::default(), &[])
+                .expect("aws", &["--profile", "dev", "sso", "login"])
+                .output("Successfully logged into Start URL\n"),
+        );
+
+        let result = Subcommand {}.execute(&context);
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output.to_string_lossy(),
+            "Successfully logged into Start URL\n"
+        );
+    }
 }
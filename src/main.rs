@@ -15,25 +15,34 @@ use clap::Parser;
 /// Primary entrypoint to the command line interface.
 ///
 /// A new [`app::Application`] instance is created from any command line arguments that may have
-/// been provided by the user. If an instance was successfully constructed, an attempt is made to
-/// execute the requested subcommand. If the subcommand returns an error response, a clean up
-/// operation is performed before the process exits with the error that was returned. If the
-/// `app::Application` instance could not be created, a clean up operation is still performed
-/// before exiting with the `clap::Error` returned by [`structopt::StructOpt::from_args_safe`].
+/// been provided by the user. If an instance was successfully constructed, the settings are loaded
+/// and an attempt is made to execute the requested subcommand. If the subcommand returns an error
+/// response, a clean up operation is performed before the process exits with the error that was
+/// returned. If the `app::Application` instance could not be created, a clean up operation is
+/// still performed before exiting with the `clap::Error` returned by
+/// [`structopt::StructOpt::from_args_safe`].
+///
+/// The same clean up operation is performed if the application is stopped by a signal while no
+/// command is running (see [`util::run`]).
 fn main() {
     let mut app = app::Application::try_parse();
 
     if let Ok(app) = app.as_mut() {
-        if let Err(error) = app.execute() {
-            // NOTE Opening for clean up.
+        if let Err(error) = app.load_settings().and_then(|_| app.execute()) {
+            clean_up();
 
             error.exit()
         }
     }
 
-    // NOTE Another opening for clean up.
+    clean_up();
 
     if let Err(error) = app.as_ref() {
         error.exit();
     }
 }
+
+/// Cleans up anything left behind before the process exits.
+fn clean_up() {
+    util::term::restore();
+}
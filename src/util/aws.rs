@@ -0,0 +1,173 @@
+//! Reads the files managed by the AWS CLI without invoking it.
+//!
+//! Invoking the AWS CLI takes a noticeable amount of time, which is not acceptable for things that
+//! run frequently such as rendering the shell prompt. The functions in this module read the AWS
+//! CLI configuration file and SSO token cache directly instead.
+
+use crate::util::config::AWS_CONFIG_DIR;
+use std::collections::HashMap;
+use std::{env, fs, path, time};
+
+/// The settings found in an AWS CLI configuration file, grouped by section name.
+pub type Sections = HashMap<String, HashMap<String, String>>;
+
+/// Returns the path to the AWS CLI configuration file.
+///
+/// The `AWS_CONFIG_FILE` environment variable is respected the same way the AWS CLI does.
+fn get_config_path() -> path::PathBuf {
+    env::var_os("AWS_CONFIG_FILE")
+        .map(path::PathBuf::from)
+        .unwrap_or_else(|| AWS_CONFIG_DIR.join("config"))
+}
+
+/// Reads the settings of an AWS CLI profile from the configuration file.
+///
+/// If the configuration file or profile does not exist, no settings are returned.
+pub fn get_profile_settings(profile: &str) -> HashMap<String, String> {
+    let sections = read_config();
+
+    get_profile_section(&sections, profile)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Returns the section containing the settings of an AWS CLI profile.
+fn get_profile_section<'a>(
+    sections: &'a Sections,
+    profile: &str,
+) -> Option<&'a HashMap<String, String>> {
+    if profile == "default" {
+        sections.get("default")
+    } else {
+        sections.get(&format!("profile {}", profile))
+    }
+}
+
+/// Returns the time the cached SSO session for an AWS CLI profile expires.
+///
+/// The SSO start URL is taken from the profile, or the SSO session it refers to, and is used to
+/// find the access token in the SSO token cache. If the profile does not use SSO, or the user has
+/// not logged in yet, [`None`] is returned.
+pub fn get_session_expiry(profile: &str) -> Option<time::SystemTime> {
+    let sections = read_config();
+    let settings = get_profile_section(&sections, profile)?;
+    let start_url = match settings.get("sso_session") {
+        Some(session) => sections
+            .get(&format!("sso-session {}", session))?
+            .get("sso_start_url")?,
+        None => settings.get("sso_start_url")?,
+    };
+
+    fs::read_dir(AWS_CONFIG_DIR.join("sso").join("cache"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .filter(|token| {
+            token.get("accessToken").is_some()
+                && token.get("startUrl").and_then(|url| url.as_str()) == Some(start_url)
+        })
+        .filter_map(|token| {
+            token
+                .get("expiresAt")
+                .and_then(|expires| expires.as_str())
+                .and_then(parse_timestamp)
+        })
+        .max()
+}
+
+/// Parses the contents of an AWS CLI configuration file.
+///
+/// The file uses the INI format, where each section contains a list of `key = value` settings.
+/// Nested settings (e.g. for S3) are flattened into the section they appear in.
+fn parse_config(contents: &str) -> Sections {
+    let mut sections = Sections::new();
+    let mut current = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            if let Some(settings) = sections.get_mut(section) {
+                settings.insert(key.trim().to_owned(), value.trim().to_owned());
+            }
+        }
+    }
+
+    sections
+}
+
+/// Parses an ISO 8601 timestamp, as used by the SSO token cache, into a [`time::SystemTime`].
+///
+/// The AWS CLI always records the time in UTC, so only the date and time are parsed and anything
+/// that follows the seconds (e.g. `Z`, `UTC`, or fractional seconds) is ignored.
+fn parse_timestamp(value: &str) -> Option<time::SystemTime> {
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+
+    let year = number(0..4)?;
+    let month = number(5..7)?;
+    let day = number(8..10)?;
+    let hour = number(11..13)?;
+    let minute = number(14..16)?;
+    let second = number(17..19)?;
+
+    // Calculates the number of days since the Unix epoch for the civil date.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+
+    if seconds < 0 {
+        return None;
+    }
+
+    Some(time::UNIX_EPOCH + time::Duration::from_secs(seconds as u64))
+}
+
+/// Reads and parses the AWS CLI configuration file.
+///
+/// If the file does not exist or could not be read, no sections are returned.
+fn read_config() -> Sections {
+    fs::read_to_string(get_config_path())
+        .map(|contents| parse_config(&contents))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn config_parsing() {
+        let sections = parse_config(
+            "[default]\nregion = us-east-1\n\n# A comment.\n[profile  dev]\nsso_session=corp\ns3 =\n  max_concurrent_requests = 20\n",
+        );
+
+        assert_eq!(sections["default"]["region"], "us-east-1");
+        assert_eq!(sections["profile dev"]["sso_session"], "corp");
+        assert_eq!(sections["profile dev"]["max_concurrent_requests"], "20");
+    }
+
+    #[test]
+    fn timestamp_parsing() {
+        let expected = time::UNIX_EPOCH + time::Duration::from_secs(1709210096);
+
+        assert_eq!(parse_timestamp("2024-02-29T12:34:56Z"), Some(expected));
+        assert_eq!(parse_timestamp("2024-02-29T12:34:56UTC"), Some(expected));
+        assert_eq!(parse_timestamp("2024-02-29T12:34:56.789Z"), Some(expected));
+        assert_eq!(parse_timestamp("not a timestamp"), None);
+    }
+}
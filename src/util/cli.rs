@@ -0,0 +1,178 @@
+//! Checks that a supported version of the AWS CLI is used.
+//!
+//! Version 1 of the AWS CLI does not support many of the commands used by the subcommands (e.g.
+//! `configure list-profiles` or `sso login`), and fails in ways that are difficult to understand.
+//! The version is checked before the AWS CLI is first used so that a clear message can be shown
+//! instead.
+//!
+//! Invoking the AWS CLI takes a noticeable amount of time, so the version that was found is cached
+//! in the configuration directory until the AWS CLI binary is replaced (e.g. upgraded).
+
+use crate::app::Application;
+use crate::util::config::CONFIG_DIR;
+use crate::util::run::Run;
+use carli::err;
+use carli::error::Result;
+use std::{fs, path, time};
+use which::which;
+
+/// The oldest major version of the AWS CLI that is supported.
+const MINIMUM_VERSION: u32 = 2;
+
+/// The amount of time the AWS CLI is given to report its version.
+const VERSION_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+
+lazy_static::lazy_static! {
+    /// The path to the file used to cache the version of the AWS CLI.
+    static ref CACHE_FILE: path::PathBuf = CONFIG_DIR.join("aws-cli.json");
+}
+
+/// The version of the AWS CLI found for a binary.
+#[derive(PartialEq, serde::Deserialize, serde::Serialize)]
+struct Cache {
+    /// The time the binary was last modified, in seconds since the Unix epoch.
+    modified: u64,
+
+    /// The path to the binary.
+    path: path::PathBuf,
+
+    /// The version of the AWS CLI.
+    version: String,
+}
+
+/// Checks that the AWS CLI used by the application is a supported version.
+///
+/// The check is only performed once for each instance of the application. If the version of the
+/// AWS CLI was cached for the same binary, the AWS CLI is not invoked.
+pub fn check(context: &Application) -> Result<()> {
+    if context.aws_cli_checked() {
+        return Ok(());
+    }
+
+    let program = context.aws_cli();
+    let path = match which(program) {
+        Ok(path) => path,
+        Err(_) => err!(
+            1,
+            "The AWS CLI, {}, could not be found. Please install version {} or later of the AWS CLI, or provide its location using --aws-cli or the aws_cli setting.",
+            program,
+            MINIMUM_VERSION
+        ),
+    };
+
+    let modified = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(time::UNIX_EPOCH).ok())
+        .map(|modified| modified.as_secs())
+        .unwrap_or_default();
+
+    let cached = fs::read_to_string(&*CACHE_FILE)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Cache>(&contents).ok())
+        .filter(|cache| cache.path == path && cache.modified == modified);
+
+    let version = match cached {
+        Some(cache) => cache.version,
+        None => {
+            let output = Run::new(program)
+                .arg("--version")
+                .read_only()
+                .timeout(VERSION_TIMEOUT)
+                .output(context)
+                .unwrap_or_default();
+
+            let version = match parse_version(&output) {
+                Some(version) => version,
+                None => err!(
+                    1,
+                    "The version of the AWS CLI, {}, could not be determined. Version {} or later of the AWS CLI is required.",
+                    path.display(),
+                    MINIMUM_VERSION
+                ),
+            };
+
+            let cache = Cache {
+                modified,
+                path: path.clone(),
+                version: version.to_owned(),
+            };
+
+            // The version is checked again next time if it could not be cached.
+            if let Ok(contents) = serde_json::to_string(&cache) {
+                let _ = fs::write(&*CACHE_FILE, contents);
+            }
+
+            version.to_owned()
+        }
+    };
+
+    check_version(&version, &path)?;
+
+    context.set_aws_cli_checked();
+
+    Ok(())
+}
+
+/// Checks that the version of the AWS CLI is supported.
+fn check_version(version: &str, path: &path::Path) -> Result<()> {
+    let major = version
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<u32>().ok())
+        .unwrap_or_default();
+
+    if major < MINIMUM_VERSION {
+        err!(
+            1,
+            "The AWS CLI, {}, is version {}, but version {} or later is required. Please install version {} or later of the AWS CLI, or provide the location of one using --aws-cli or the aws_cli setting.",
+            path.display(),
+            version,
+            MINIMUM_VERSION,
+            MINIMUM_VERSION
+        );
+    }
+
+    Ok(())
+}
+
+/// Parses the version of the AWS CLI from the output of `aws --version`.
+///
+/// The output begins with the version of the AWS CLI, followed by the versions of its
+/// dependencies (e.g. `aws-cli/2.15.0 Python/3.11.6 Linux/6.5.0 exe/x86_64.ubuntu.22`).
+fn parse_version(output: &str) -> Option<&str> {
+    output
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix("aws-cli/"))
+        .filter(|version| !version.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn version_checking() {
+        let path = path::Path::new("/usr/local/bin/aws");
+
+        assert!(check_version("2.15.0", path).is_ok());
+        assert!(check_version("3.0.0", path).is_ok());
+        assert_eq!(
+            format!("{}", check_version("1.22.34", path).unwrap_err()),
+            "The AWS CLI, /usr/local/bin/aws, is version 1.22.34, but version 2 or later is required. Please install version 2 or later of the AWS CLI, or provide the location of one using --aws-cli or the aws_cli setting.\n"
+        );
+    }
+
+    #[test]
+    fn version_parsing() {
+        assert_eq!(
+            parse_version("aws-cli/2.15.0 Python/3.11.6 Linux/6.5.0 exe/x86_64.ubuntu.22\n"),
+            Some("2.15.0")
+        );
+        assert_eq!(
+            parse_version("aws-cli/1.22.34 Python/3.10.12 Linux/6.5.0 botocore/1.23.34"),
+            Some("1.22.34")
+        );
+        assert_eq!(parse_version("command not found"), None);
+    }
+}
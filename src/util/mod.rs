@@ -1,5 +1,7 @@
 //! Provides miscellaneous utilities that are shared by subcommands and test suites.
 
+pub mod aws;
+pub mod cli;
 pub mod config;
 pub mod run;
 pub mod shell;
@@ -1,38 +1,244 @@
 //! Provides a simplified and well integrated interface to [`Command`].
 
 use crate::app::Application;
-use carli::err;
+use crate::util::cli;
+use crate::util::config::CONFIG_DIR;
+use crate::util::shell::quote;
 use carli::error::{Error, Result};
-use carli::io::Shared;
-use std::collections::HashMap;
+use carli::io::{Shared, Stream};
+use carli::{err, error, errorln, outputln};
+use futures_util::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::io::Write;
-use std::process::Stdio;
-use std::sync::Mutex;
-use tokio::io::AsyncReadExt;
+use std::pin::Pin;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, Once};
+use std::{cell, env, fmt, fs, path, process, time};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::join;
-use tokio::process::Command;
+use tokio::process::{Child, Command};
 use tokio::runtime::Runtime;
 use which::which;
 
+/// The size of the buffer used to stream the output of a command.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The exit status used when a command is stopped because it did not finish in time.
+const TIMEOUT_STATUS: i32 = 124;
+
+/// The placeholder shown in place of a secret when a command is displayed.
+const REDACTED: &str = "'<redacted>'";
+
+/// The ASCII punctuation that does not need to be quoted when a command is displayed.
+const SAFE_CHARS: &str = "_-./:,@%+=";
+
+/// Used to start listening for signals when the first command is run.
+static LISTEN: Once = Once::new();
+
+/// The signal that was received to stop the application, or zero if none has been received.
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
 lazy_static::lazy_static! {
     /// Caches the check performed for each program in `PATH`.
     static ref CHECK_CACHE: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+
+    /// The IDs of the processes that are running.
+    static ref CHILDREN: Mutex<HashSet<u32>> = Mutex::new(HashSet::new());
+
+    /// The path to the file the commands that are run are logged to.
+    static ref LOG_FILE: path::PathBuf = CONFIG_DIR.join("commands.log");
+
+    /// The runtime shared by all of the commands that are run.
+    static ref RUNTIME: Runtime = Runtime::new()
+        .expect("The runtime used to run commands could not be created.");
+}
+
+/// A command that is being run, which resolves once the command has exited.
+pub type Task<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + 'a>>;
+
+/// Executes the commands built using [`Run`].
+///
+/// The executor used is owned by [`Application`], which allows the commands to be replaced with a
+/// [`Script`] when testing the subcommands without the programs they depend on.
+pub trait Executor {
+    /// Runs the command using the standard streams of the current process.
+    fn interactive(&self, run: &mut Run) -> Result<()>;
+
+    /// Runs the command and returns its output.
+    ///
+    /// Unlike the other methods, the command is not run until the returned [`Task`] is awaited,
+    /// which allows several commands to be run at the same time.
+    fn output<'a>(&'a self, run: &'a mut Run) -> Task<'a, String>;
+
+    /// Runs the command and passes its output through the context streams.
+    fn pass_through(&self, run: &mut Run, context: &Application) -> Result<()>;
+}
+
+/// Executes commands by spawning a new process for each of them.
+pub struct Process;
+
+impl Executor for Process {
+    fn interactive(&self, run: &mut Run) -> Result<()> {
+        prepare(run)?;
+
+        RUNTIME.block_on(async {
+            let mut child = run
+                .builder
+                .stderr(Stdio::inherit())
+                .stdin(get_stdin(&run.input))
+                .stdout(Stdio::inherit())
+                .spawn()?;
+
+            let stdin = child.stdin.take();
+
+            let (result, input) = join!(
+                wait(&mut child, run.timeout),
+                write_input(stdin, run.input.as_deref())
+            );
+
+            let status = result?;
+
+            input?;
+
+            run.status = Some(get_code(status));
+
+            if !status.success() {
+                err!(get_code(status));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn output<'a>(&'a self, run: &'a mut Run) -> Task<'a, String> {
+        Box::pin(async move {
+            prepare(run)?;
+
+            let mut child = run
+                .builder
+                .stderr(Stdio::piped())
+                .stdin(get_stdin(&run.input))
+                .stdout(Stdio::piped())
+                .spawn()?;
+
+            let stderr = child.stderr.take();
+            let stdin = child.stdin.take();
+            let stdout = child.stdout.take();
+
+            let (result, error, input, output) = join!(
+                wait(&mut child, run.timeout),
+                read_all(stderr),
+                write_input(stdin, run.input.as_deref()),
+                read_all(stdout)
+            );
+
+            let status = result?;
+
+            input?;
+
+            run.status = Some(get_code(status));
+
+            if status.success() {
+                Ok(String::from_utf8_lossy(&output?).to_string())
+            } else {
+                err!(get_code(status), "{}", String::from_utf8_lossy(&error?));
+            }
+        })
+    }
+
+    fn pass_through(&self, run: &mut Run, context: &Application) -> Result<()> {
+        prepare(run)?;
+
+        RUNTIME.block_on(async {
+            let mut child = run
+                .builder
+                .stderr(Stdio::piped())
+                .stdin(get_stdin(&run.input))
+                .stdout(Stdio::piped())
+                .spawn()?;
+
+            let stderr = child.stderr.take();
+            let stdin = child.stdin.take();
+            let stdout = child.stdout.take();
+
+            let (result, error, input, output) = join!(
+                wait(&mut child, run.timeout),
+                stream(stderr, || context.error()),
+                write_input(stdin, run.input.as_deref()),
+                stream(stdout, || context.output())
+            );
+
+            let status = result?;
+
+            error?;
+            input?;
+            output?;
+
+            run.status = Some(get_code(status));
+
+            if !status.success() {
+                err!(get_code(status));
+            }
+
+            Ok(())
+        })
+    }
 }
 
 /// Simplifies the building of a new [`Command`] instance.
 pub struct Run {
     /// The arguments used with the builder.
-    #[cfg(debug_assertions)]
     arguments: Vec<String>,
 
+    /// The flag used to indicate that the AWS CLI is being invoked.
+    aws: bool,
+
     /// The process builder.
     builder: Command,
 
+    /// The environment variables set for the process.
+    envs: Vec<(String, String)>,
+
+    /// The data written to the standard input stream of the process.
+    input: Option<String>,
+
     /// The name of the program.
     program: String,
+
+    /// The flag used to indicate that the command does not change anything.
+    read_only: bool,
+
+    /// The positions of the arguments that are secrets.
+    secrets: Vec<usize>,
+
+    /// The exit status of the process, once it has exited.
+    status: Option<i32>,
+
+    /// The amount of time the process is given to exit before it is killed.
+    timeout: Option<time::Duration>,
 }
 
 impl Run {
+    /// Creates a new instance for the AWS CLI used by the application.
+    ///
+    /// ```
+    /// let mut run = Run::aws(&context)
+    ///     .arg("eks")
+    ///     .arg("list-clusters");
+    /// ```
+    ///
+    /// The AWS CLI may be found somewhere other than PATH if the user provided its location. Before
+    /// the command is first run, the version of the AWS CLI is checked to make sure it is
+    /// supported.
+    pub fn aws(context: &Application) -> Self {
+        let mut run = Self::new(context.aws_cli());
+
+        run.aws = true;
+        run
+    }
+
     /// Adds an argument to the process builder.
     ///
     /// ```
@@ -43,13 +249,62 @@ impl Run {
     /// ```
     pub fn arg(&mut self, arg: &str) -> &mut Self {
         self.builder.arg(arg);
-
-        #[cfg(debug_assertions)]
         self.arguments.push(arg.to_owned());
 
         self
     }
 
+    /// Checks the version of the AWS CLI if it is about to be invoked.
+    ///
+    /// This must be done before the runtime is used, since the check also runs a command.
+    fn check_cli(&self, context: &Application) -> Result<()> {
+        if self.aws && (self.read_only || !context.dry_run()) {
+            cli::check(context)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the command instead of running it if the user asked for a dry run.
+    ///
+    /// Commands that are read only are still run so that anything that depends on their output is
+    /// able to carry on as usual. If the command must not be run, `true` is returned.
+    fn dry_run(&self, context: &Application) -> Result<bool> {
+        if !context.dry_run() || self.read_only {
+            return Ok(false);
+        }
+
+        outputln!(context, "{}", self)?;
+
+        Ok(true)
+    }
+
+    /// Sets an environment variable for the process.
+    ///
+    /// ```
+    /// let mut run = Run::new("my-app")
+    ///     .env("AWS_PROFILE", "my-profile");
+    /// ```
+    pub fn env(&mut self, name: &str, value: &str) -> &mut Self {
+        self.builder.env(name, value);
+        self.envs.push((name.to_owned(), value.to_owned()));
+
+        self
+    }
+
+    /// Removes an environment variable that would otherwise be inherited by the process.
+    ///
+    /// ```
+    /// let mut run = Run::new("my-app")
+    ///     .env_remove("AWS_PROFILE");
+    /// ```
+    pub fn env_remove(&mut self, name: &str) -> &mut Self {
+        self.builder.env_remove(name);
+        self.envs.retain(|(n, _)| n != name);
+
+        self
+    }
+
     /// Returns the arguments added to the builder.
     ///
     /// ```
@@ -65,6 +320,78 @@ impl Run {
         &self.arguments
     }
 
+    /// Writes the data to the standard input stream of the process instead of inheriting it.
+    ///
+    /// This should be used to provide secrets (e.g. passwords) to the command, which would
+    /// otherwise be visible to other users and processes when passed as an argument.
+    ///
+    /// ```
+    /// let mut run = Run::new("docker")
+    ///     .arg("login")
+    ///     .arg("--password-stdin")
+    ///     .input("my-password");
+    /// ```
+    pub fn input(&mut self, input: &str) -> &mut Self {
+        self.input = Some(input.to_owned());
+
+        self
+    }
+
+    /// Runs the command using the standard streams of the current process.
+    ///
+    /// ```
+    /// run::Run::new("kubectl")
+    ///     .arg("exec")
+    ///     .arg("-it")
+    ///     .arg("my-pod")
+    ///     .arg("--")
+    ///     .arg("sh")
+    ///     .interactive(&context)?;
+    /// ```
+    ///
+    /// Unlike [`Run::pass_through`], the streams are inherited instead of being piped so that the
+    /// command is able to interact with the terminal. If the command exits with a non-zero status,
+    /// an [`Err`] for [`Result`] will be returned using the same status.
+    pub fn interactive(&mut self, context: &Application) -> Result<()> {
+        if self.dry_run(context)? {
+            return Ok(());
+        }
+
+        self.check_cli(context)?;
+
+        let started = self.trace_started(context)?;
+        let result = context.executor().interactive(self);
+
+        self.trace_finished(context, started)?;
+
+        result
+    }
+
+    /// Runs the AWS CLI command and deserializes its JSON output.
+    ///
+    /// ```
+    /// #[derive(serde::Deserialize)]
+    /// struct Clusters {
+    ///     clusters: Vec<String>,
+    /// }
+    ///
+    /// let clusters = Run::aws(&context)
+    ///     .with_aws_options(&context)
+    ///     .arg("eks")
+    ///     .arg("list-clusters")
+    ///     .json::<Clusters>(&context)?;
+    /// ```
+    ///
+    /// The `--output json` option is added so that the output does not depend on the output format
+    /// configured for the AWS CLI profile. If the command fails, or the output does not have the
+    /// expected shape, an [`Err`] for [`Result`] will be returned. It is recommended that context
+    /// be added for these errors.
+    pub fn json<T: serde::de::DeserializeOwned>(&mut self, context: &Application) -> Result<T> {
+        let output = self.arg("--output").arg("json").output(context)?;
+
+        parse_json(&output)
+    }
+
     /// Creates a new instance for the specified command line application.
     ///
     /// ```
@@ -72,10 +399,16 @@ impl Run {
     /// ```
     pub fn new(name: &str) -> Self {
         Self {
-            #[cfg(debug_assertions)]
             arguments: Vec::new(),
+            aws: false,
             builder: Command::new(name),
+            envs: Vec::new(),
+            input: None,
             program: name.to_owned(),
+            read_only: false,
+            secrets: Vec::new(),
+            status: None,
+            timeout: None,
         }
     }
 
@@ -89,11 +422,11 @@ impl Run {
     /// let app = app::Application::from_args();
     /// let mut context = app::ApplicationContext::new(&app);
     ///
-    /// let output = run::Run::new("aws")
+    /// let output = run::Run::aws(&context)
     ///     .with_aws_options(&context)
     ///     .arg("eks")
     ///     .arg("list-clusters")
-    ///     .output()?;
+    ///     .output(&context)?;
     ///
     /// println!("{}", output);
     /// ```
@@ -101,25 +434,60 @@ impl Run {
     /// If the command exits with a non-zero status, an [`Err`] for [`Result`] will be returned,
     /// with the error output being used as the message. It is recommended that context be added
     /// for these errors.
-    pub fn output(&mut self) -> Result<String> {
-        if !in_path(&self.program)? {
-            err!(1, "The program, {}, could be found in PATH.", self.program);
+    pub fn output(&mut self, context: &Application) -> Result<String> {
+        self.check_cli(context)?;
+
+        RUNTIME.block_on(self.output_task(context))
+    }
+
+    /// Runs the commands at the same time and returns the output of each of them, in order.
+    ///
+    /// ```
+    /// let runs = ["sso_region", "sso_start_url"].map(|key| {
+    ///     let mut run = Run::aws(&context);
+    ///
+    ///     run.arg("configure").arg("get").arg(key);
+    ///     run
+    /// });
+    ///
+    /// let [region, start_url] = Run::output_all(&context, runs);
+    /// ```
+    ///
+    /// The result for each command is the same as it would have been for [`Run::output`], so an
+    /// error for one of the commands does not prevent the others from being run.
+    pub fn output_all<const N: usize>(
+        context: &Application,
+        mut runs: [Run; N],
+    ) -> [Result<String>; N] {
+        let checks: Vec<Result<()>> = runs.iter().map(|run| run.check_cli(context)).collect();
+        let results = RUNTIME.block_on(join_all(runs.iter_mut().zip(checks).map(
+            |(run, check)| async move {
+                check?;
+
+                run.output_task(context).await
+            },
+        )));
+
+        match results.try_into() {
+            Ok(results) => results,
+            Err(_) => unreachable!("There is a result for each command."),
         }
+    }
 
-        Runtime::new()?.block_on(async {
-            let output = self.builder.stdin(Stdio::inherit()).output().await?;
+    /// Runs the command and resolves to its output once it has exited.
+    async fn output_task(&mut self, context: &Application) -> Result<String> {
+        if self.dry_run(context)? {
+            return Ok(String::new());
+        }
 
-            if output.status.success() {
-                let string = String::from_utf8_lossy(output.stdout.as_slice());
+        self.timeout = self.timeout.or_else(|| context.timeout());
 
-                Ok((*string).to_string())
-            } else {
-                let message = format!("{}", String::from_utf8_lossy(output.stderr.as_slice()));
-                let status = output.status.code().unwrap_or(1);
+        let started = self.trace_started(context)?;
+        let result = context.executor().output(self).await;
 
-                err!(status, message);
-            }
-        })
+        self.trace_finished(context, started)?;
+
+        result
     }
 
     /// Runs the command and passes its output through the context streams.
@@ -132,85 +500,122 @@ impl Run {
     /// let app = app::Application::from_args();
     /// let mut context = app::ApplicationContext::new(&app);
     ///
-    /// run::Run::new("aws")
+    /// run::Run::aws(&context)
     ///     .with_aws_options(&context)
     ///     .arg("eks")
     ///     .arg("list-clusters")
     ///     .pass_through(&mut context)?;
     /// ```
     pub fn pass_through(&mut self, context: &Application) -> Result<()> {
-        if !in_path(&self.program)? {
-            err!(1, "The program, {}, could be found in PATH.", self.program);
+        if self.dry_run(context)? {
+            return Ok(());
         }
 
-        Runtime::new()?.block_on(async {
-            let mut child = self
-                .builder
-                .stderr(Stdio::piped())
-                .stdin(Stdio::inherit())
-                .stdout(Stdio::piped())
-                .spawn()?;
+        self.check_cli(context)?;
+        self.timeout = self.timeout.or_else(|| context.timeout());
 
-            let stderr = child.stderr.take();
-            let stdout = child.stdout.take();
+        let started = self.trace_started(context)?;
+        let result = context.executor().pass_through(self, context);
 
-            let (result, _, _) = join!(
-                child.wait(),
-                async {
-                    if let Some(mut source) = stderr {
-                        let mut buffer = vec![0];
-                        let mut target = context.error();
-
-                        loop {
-                            match source.read(&mut buffer).await {
-                                Ok(0) => break,
-                                Ok(_) => {
-                                    target.write_all(&buffer)?;
-                                    target.flush()?;
-                                }
-                                Err(error) => err!(1, "{}", error),
-                            }
-                        }
-                    }
-
-                    Ok(())
-                },
-                async {
-                    if let Some(mut source) = stdout {
-                        let mut buffer = vec![0];
-                        let mut target = context.output();
-
-                        loop {
-                            match source.read(&mut buffer).await {
-                                Ok(0) => break,
-                                Ok(_) => {
-                                    target.write_all(&buffer)?;
-                                    target.flush()?;
-                                }
-                                Err(error) => err!(1, "{}", error),
-                            }
-                        }
-                    }
-
-                    Ok(())
-                }
-            );
+        self.trace_finished(context, started)?;
 
-            let status = result?;
+        result
+    }
 
-            if !status.success() {
-                err!(status.code().unwrap_or(1));
-            }
+    /// Marks the command as one that does not change anything.
+    ///
+    /// Read only commands are run even if the user asked for a dry run, which allows the commands
+    /// used to discover things (e.g. the list of clusters) to provide what later commands need.
+    ///
+    /// ```
+    /// let mut run = Run::aws(&context)
+    ///     .arg("eks")
+    ///     .arg("list-clusters")
+    ///     .read_only();
+    /// ```
+    pub fn read_only(&mut self) -> &mut Self {
+        self.read_only = true;
 
-            Ok(())
-        })
+        self
+    }
+
+    /// Adds an argument that is redacted whenever the command is displayed.
+    ///
+    /// ```
+    /// let mut run = Run::aws(&context)
+    ///     .arg("configure")
+    ///     .arg("set")
+    ///     .arg("aws_secret_access_key")
+    ///     .secret_arg("my-secret");
+    /// ```
+    pub fn secret_arg(&mut self, arg: &str) -> &mut Self {
+        self.secrets.push(self.arguments.len());
+
+        self.arg(arg)
+    }
+
+    /// Sets the amount of time the command is given to finish before it is stopped.
+    ///
+    /// ```
+    /// let mut run = Run::aws(&context)
+    ///     .arg("sso")
+    ///     .arg("login")
+    ///     .timeout(time::Duration::from_secs(300));
+    /// ```
+    ///
+    /// If a timeout is not set, the one configured for the application is used, unless the command
+    /// is run using [`Run::interactive`]. An interactive command may be waiting on the user, so it
+    /// is only stopped if a timeout was set for it.
+    pub fn timeout(&mut self, timeout: time::Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+
+        self
+    }
+
+    /// Traces the command once it has finished, if the user asked for it.
+    ///
+    /// The trace includes how long it took for the command to finish and its exit status.
+    fn trace_finished(
+        &self,
+        context: &Application,
+        started: Option<(String, time::Instant)>,
+    ) -> Result<()> {
+        if let Some((command, started)) = started {
+            let elapsed = started.elapsed().as_secs_f64();
+            let message = match self.status {
+                Some(status) => format!("{} (exit status {}, {:.3}s)", command, status, elapsed),
+                None => format!("{} (not run, {:.3}s)", command, elapsed),
+            };
+
+            trace(context, 1, &message)?;
+        }
+
+        Ok(())
+    }
+
+    /// Traces the command before it is run, if the user asked for it.
+    ///
+    /// The command is only shown at the second level of verbosity, so that a command that does not
+    /// finish can be identified. The displayed command and the time it was started is returned so
+    /// that it can be traced again by [`Run::trace_finished`].
+    fn trace_started(&self, context: &Application) -> Result<Option<(String, time::Instant)>> {
+        if context.verbose() == 0 && !context.log() {
+            return Ok(None);
+        }
+
+        let command = self.to_string();
+
+        trace(context, 2, &format!("Running: {}", command))?;
+
+        Ok(Some((command, time::Instant::now())))
     }
 
     /// Assumes that the AWS CLI is being invoked and adds additional arguments.
     ///
     /// The given context will be used to add the `--profile` and `--region` options for the AWS
     /// CLI if the user has provided any. This will allow the AWS CLI to inherit any profile and
-    /// region information provided to this application.
+    /// region information provided to this application. If a region was not provided, the region
+    /// setting is used when the AWS CLI would not otherwise have a region to use.
     ///
     /// ```
     /// use crate::app;
@@ -219,7 +624,7 @@ impl Run {
     /// let app = app::Application::from_args();
     /// let context = app::ApplicationContext::new(&app);
     ///
-    /// Run::new("aws")
+    /// Run::aws(&context)
     ///     .with_aws_options(&context)
     ///     .arg("configure")
     ///     .arg("get")
@@ -230,7 +635,7 @@ impl Run {
             self.arg("--profile").arg(profile);
         }
 
-        if let Some(region) = context.region() {
+        if let Some(region) = context.region().or_else(|| get_default_region(context)) {
             self.arg("--region").arg(region);
         }
 
@@ -238,49 +643,597 @@ impl Run {
     }
 }
 
-/// Checks if a program can be found in `PATH`.
-fn in_path(program: &str) -> Result<bool> {
-    let mut cache = match CHECK_CACHE.lock() {
-        Ok(cache) => cache,
-        Err(error) => {
-            return Err(Error::new(1)
-                .message(format!("{}", error))
-                .context("Could not acquire lock on CHECK_CACHE.".to_owned()))
+impl fmt::Display for Run {
+    /// Displays the command the way it would be typed into a POSIX shell.
+    ///
+    /// Secret arguments and the data written to the standard input stream are redacted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.envs {
+            write!(f, "{}={} ", name, quote_word(value))?;
         }
-    };
 
-    if let Some(status) = cache.get(program) {
-        Ok(*status)
-    } else {
-        let status = which(program).is_ok();
+        write!(f, "{}", quote_word(&self.program))?;
 
-        cache.insert(program.to_owned(), status);
+        for (index, arg) in self.arguments.iter().enumerate() {
+            if self.secrets.contains(&index) {
+                write!(f, " {}", REDACTED)?;
+            } else {
+                write!(f, " {}", quote_word(arg))?;
+            }
+        }
 
-        Ok(status)
+        if self.input.is_some() {
+            write!(f, " <<< {}", REDACTED)?;
+        }
+
+        Ok(())
     }
 }
 
+/// An executor that plays back a script of expected commands instead of running them.
+///
+/// Each command is checked against the next command expected by the script, and the canned output,
+/// error output, and exit status of the expected command are used as its result. The test fails if
+/// any of the expected commands were not run by the time the executor is dropped.
+///
+/// ```
+/// let context = Application::test(None, None).with_executor(
+///     Script::default()
+///         .expect("aws", &["configure", "list-profiles"])
+///         .output("dev\nprod\n"),
+/// );
+/// ```
 #[cfg(test)]
-mod test {
-    use super::*;
+#[derive(Default)]
+pub struct Script {
+    /// The commands that have yet to be run.
+    expected: cell::RefCell<std::collections::VecDeque<Expected>>,
+}
 
-    #[cfg(unix)]
-    use std::io::Seek;
+/// A command expected by a [`Script`], and its canned result.
+#[cfg(test)]
+#[derive(Default)]
+struct Expected {
+    /// The arguments the command is expected to be run with.
+    args: Vec<String>,
 
-    #[test]
-    fn argument_building() {
-        let args = Run::new("test")
-            .arg("arg1")
-            .arg("arg2")
-            .arg("arg3")
-            .get_args()
-            .to_owned();
+    /// The environment variables the command is expected to be run with.
+    envs: Vec<(String, String)>,
 
-        assert_eq!(args.as_ref(), vec!["arg1", "arg2", "arg3"]);
-    }
+    /// The error output of the command.
+    error: String,
 
-    #[test]
-    fn aws_options_added() {
+    /// The data the command is expected to be given through its standard input stream.
+    input: Option<String>,
+
+    /// The output of the command.
+    output: String,
+
+    /// The name of the program expected to be run.
+    program: String,
+
+    /// The exit status of the command.
+    status: i32,
+}
+
+#[cfg(test)]
+impl Script {
+    /// Adds an environment variable the last expected command is expected to be run with.
+    pub fn env(mut self, name: &str, value: &str) -> Self {
+        self.last().envs.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Sets the error output of the last expected command.
+    pub fn error(mut self, error: &str) -> Self {
+        self.last().error = error.to_owned();
+        self
+    }
+
+    /// Adds a command that is expected to be run after the previously expected commands.
+    pub fn expect(mut self, program: &str, args: &[&str]) -> Self {
+        self.expected.get_mut().push_back(Expected {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            program: program.to_owned(),
+            ..Default::default()
+        });
+
+        self
+    }
+
+    /// Sets the data the last expected command is given through its standard input stream.
+    pub fn input(mut self, input: &str) -> Self {
+        self.last().input = Some(input.to_owned());
+        self
+    }
+
+    /// Returns the last expected command.
+    fn last(&mut self) -> &mut Expected {
+        self.expected
+            .get_mut()
+            .back_mut()
+            .expect("A command must be expected first.")
+    }
+
+    /// Checks the command against the next expected command and returns it.
+    fn next(&self, run: &mut Run) -> Expected {
+        let expected = match self.expected.borrow_mut().pop_front() {
+            Some(expected) => expected,
+            None => panic!(
+                "The command was not expected: {} {:?}",
+                run.program, run.arguments
+            ),
+        };
+
+        assert_eq!(run.program, expected.program);
+        assert_eq!(run.arguments, expected.args);
+        assert_eq!(run.envs, expected.envs);
+        assert_eq!(run.input, expected.input);
+
+        run.status = Some(expected.status);
+
+        expected
+    }
+
+    /// Sets the output of the last expected command.
+    pub fn output(mut self, output: &str) -> Self {
+        self.last().output = output.to_owned();
+        self
+    }
+
+    /// Sets the exit status of the last expected command.
+    pub fn status(mut self, status: i32) -> Self {
+        self.last().status = status;
+        self
+    }
+}
+
+#[cfg(test)]
+impl Drop for Script {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            let remaining = self
+                .expected
+                .get_mut()
+                .iter()
+                .map(|expected| format!("{} {:?}", expected.program, expected.args))
+                .collect::<Vec<String>>();
+
+            assert!(
+                remaining.is_empty(),
+                "The expected commands were not run: {:?}",
+                remaining
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+impl Executor for Script {
+    fn interactive(&self, run: &mut Run) -> Result<()> {
+        let expected = self.next(run);
+
+        if expected.status != 0 {
+            err!(expected.status);
+        }
+
+        Ok(())
+    }
+
+    fn output<'a>(&'a self, run: &'a mut Run) -> Task<'a, String> {
+        Box::pin(async move {
+            let expected = self.next(run);
+
+            if expected.status != 0 {
+                err!(expected.status, expected.error);
+            }
+
+            Ok(expected.output)
+        })
+    }
+
+    fn pass_through(&self, run: &mut Run, context: &Application) -> Result<()> {
+        let expected = self.next(run);
+
+        context.error().write_all(expected.error.as_bytes())?;
+        context.output().write_all(expected.output.as_bytes())?;
+
+        if expected.status != 0 {
+            err!(expected.status);
+        }
+
+        Ok(())
+    }
+}
+
+/// Copies the output of a command to one of the context streams as it is produced.
+///
+/// The output is read in large chunks to avoid a system call for every byte. The target stream is
+/// flushed whenever a chunk contains the end of a line, or when the command has paused (e.g. to
+/// prompt for input without ending the line), so that interactive output is shown immediately.
+async fn stream<'a>(
+    source: Option<impl AsyncRead + Unpin>,
+    target: impl Fn() -> cell::RefMut<'a, Stream>,
+) -> Result<()> {
+    let mut source = match source {
+        Some(source) => source,
+        None => return Ok(()),
+    };
+
+    let mut buffer = vec![0; CHUNK_SIZE];
+
+    loop {
+        let read = match source.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) => err!(1, "{}", error),
+        };
+
+        let chunk = &buffer[..read];
+        let mut target = target();
+
+        target.write_all(chunk)?;
+
+        if read < buffer.len() || chunk.contains(&b'\n') {
+            target.flush()?;
+        }
+    }
+
+    target().flush()?;
+
+    Ok(())
+}
+
+/// Returns the exit status code for a process.
+///
+/// If the process was stopped by a signal, the conventional status for the signal is used.
+fn get_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+
+    status.code().unwrap_or(1)
+}
+
+/// Returns the region setting, if the AWS CLI would not otherwise have a region to use.
+///
+/// The AWS CLI uses the region in the environment, or the one configured for the profile, before
+/// the region setting. Whether the profile configures a region is only checked once.
+fn get_default_region(context: &Application) -> Option<&str> {
+    let region = context.settings().region()?;
+
+    if env::var_os("AWS_REGION").is_some() || env::var_os("AWS_DEFAULT_REGION").is_some() {
+        return None;
+    }
+
+    let configured = match context.region_configured() {
+        Some(configured) => configured,
+        None => {
+            let mut run = Run::aws(context);
+
+            if let Some(profile) = context.profile() {
+                run.arg("--profile").arg(profile);
+            }
+
+            // The AWS CLI fails if the region is not configured, which is the same as no region.
+            let configured = run
+                .arg("configure")
+                .arg("get")
+                .arg("region")
+                .read_only()
+                .output(context)
+                .map_or(false, |region| !region.trim().is_empty());
+
+            context.set_region_configured(configured);
+
+            configured
+        }
+    };
+
+    if configured {
+        None
+    } else {
+        Some(region)
+    }
+}
+
+/// Returns the standard input stream configuration for a command.
+///
+/// The standard input stream of the current process is inherited, unless input was provided.
+fn get_stdin(input: &Option<String>) -> Stdio {
+    if input.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::inherit()
+    }
+}
+
+/// Forwards a signal to the processes of the commands that are running.
+#[cfg(unix)]
+fn forward(signal: i32, children: &HashSet<u32>) {
+    for id in children {
+        // SAFETY: Sending a signal does not affect the memory of this process.
+        unsafe {
+            libc::kill(*id as libc::pid_t, signal);
+        }
+    }
+}
+
+/// Checks if a program can be found in `PATH`.
+fn in_path(program: &str) -> Result<bool> {
+    let mut cache = match CHECK_CACHE.lock() {
+        Ok(cache) => cache,
+        Err(error) => {
+            return Err(Error::new(1)
+                .message(format!("{}", error))
+                .context("Could not acquire lock on CHECK_CACHE.".to_owned()))
+        }
+    };
+
+    if let Some(status) = cache.get(program) {
+        Ok(*status)
+    } else {
+        let status = which(program).is_ok();
+
+        cache.insert(program.to_owned(), status);
+
+        Ok(status)
+    }
+}
+
+/// Checks if this process is in the foreground process group of its terminal.
+///
+/// If none of the standard streams are attached to a terminal, the process is not in the
+/// foreground.
+#[cfg(unix)]
+fn in_foreground() -> bool {
+    for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        // SAFETY: Querying the state of the terminal does not affect the memory of this process.
+        unsafe {
+            if libc::isatty(fd) == 1 {
+                return libc::tcgetpgrp(fd) == libc::getpgrp();
+            }
+        }
+    }
+
+    false
+}
+
+/// Listens for the signals used to stop the application.
+///
+/// If a command is running when a signal is received, the signal is forwarded to the process so
+/// that it is able to stop on its own terms (see [`stop`]). Otherwise, the application is cleaned
+/// up and exits with the conventional status for the signal, as it would have if the signal had
+/// not been handled.
+#[cfg(unix)]
+async fn listen() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (mut interrupt, mut terminate) = match (
+        signal(SignalKind::interrupt()),
+        signal(SignalKind::terminate()),
+    ) {
+        (Ok(interrupt), Ok(terminate)) => (interrupt, terminate),
+        _ => return,
+    };
+
+    loop {
+        let signal = tokio::select! {
+            _ = interrupt.recv() => libc::SIGINT,
+            _ = terminate.recv() => libc::SIGTERM,
+        };
+
+        stop(signal);
+    }
+}
+
+/// Listens for the signals used to stop the application.
+///
+/// Windows sends the interrupt to every process attached to the console, so the signal does not
+/// need to be forwarded to a command that is running.
+#[cfg(not(unix))]
+async fn listen() {
+    while tokio::signal::ctrl_c().await.is_ok() {
+        stop(2);
+    }
+}
+
+/// Parses the JSON output of a command.
+///
+/// If the output is not in the expected format, an [`Err`] for [`Result`] is returned.
+pub fn parse_json<T: serde::de::DeserializeOwned>(output: &str) -> Result<T> {
+    match serde_json::from_str(output) {
+        Ok(value) => Ok(value),
+        Err(error) => err!(
+            1,
+            "The output of the command is not in the expected format: {}",
+            error
+        ),
+    }
+}
+
+/// Checks that a command is able to be run.
+///
+/// Once a signal to stop the application has been received, no other command will be run so that
+/// the application is able to exit as soon as possible.
+fn prepare(run: &Run) -> Result<()> {
+    LISTEN.call_once(|| {
+        RUNTIME.spawn(listen());
+    });
+
+    let signal = RECEIVED.load(Ordering::SeqCst);
+
+    if signal != 0 {
+        err!(128 + signal);
+    }
+
+    if !in_path(&run.program)? {
+        err!(1, "The program, {}, could be found in PATH.", run.program);
+    }
+
+    Ok(())
+}
+
+/// Quotes a word of a command for display, unless it does not need to be quoted.
+fn quote_word(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || SAFE_CHARS.contains(c))
+    {
+        word.to_owned()
+    } else {
+        quote::posix(word)
+    }
+}
+
+/// Reads all of the output of a command.
+async fn read_all(source: Option<impl AsyncRead + Unpin>) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+
+    if let Some(mut source) = source {
+        source.read_to_end(&mut buffer).await?;
+    }
+
+    Ok(buffer)
+}
+
+/// Handles a signal used to stop the application.
+///
+/// An interrupt sent by the terminal is also received by the processes in the foreground process
+/// group, which includes the commands that are running, so it is only forwarded if this process is
+/// not in the foreground (e.g. the interrupt was sent using `kill`).
+fn stop(signal: i32) {
+    RECEIVED.store(signal, Ordering::SeqCst);
+
+    let children = match CHILDREN.lock() {
+        Ok(children) => children.clone(),
+        Err(_) => HashSet::new(),
+    };
+
+    if children.is_empty() {
+        crate::clean_up();
+
+        process::exit(128 + signal);
+    }
+
+    #[cfg(unix)]
+    if signal != libc::SIGINT || !in_foreground() {
+        forward(signal, &children);
+    }
+}
+
+/// Traces a command if the user asked for it.
+///
+/// The message is written to the error stream if the verbosity is at least the given level. If
+/// logging was enabled, the message is also appended to the log file using at least the first level
+/// of verbosity.
+fn trace(context: &Application, level: u8, message: &str) -> Result<()> {
+    if context.verbose() >= level {
+        errorln!(context, "aws-login: {}", message)?;
+    }
+
+    if context.log() && context.verbose().max(1) >= level {
+        let mut file = match fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&*LOG_FILE)
+        {
+            Ok(file) => file,
+            Err(error) => {
+                return Err(Error::from(error).context(format!(
+                    "Could not write to the log file: {}",
+                    LOG_FILE.display()
+                )))
+            }
+        };
+
+        let timestamp = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        writeln!(file, "[{}] {}", timestamp, message)?;
+    }
+
+    Ok(())
+}
+
+/// Waits for the process of a command to exit.
+///
+/// If the process does not exit before the timeout, it is killed and an [`Err`] for [`Result`] is
+/// returned. Signals used to stop the application are forwarded to the process while it is running
+/// (see [`stop`]). If the process handled such a signal and exited successfully, the signal is
+/// forgotten so that the application is able to continue.
+async fn wait(child: &mut Child, timeout: Option<time::Duration>) -> Result<ExitStatus> {
+    let id = child.id();
+
+    if let (Some(id), Ok(mut children)) = (id, CHILDREN.lock()) {
+        children.insert(id);
+    }
+
+    let result = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(result) => result.map_err(Error::from),
+            Err(_) => {
+                let _ = child.kill().await;
+
+                Err(error!(
+                    TIMEOUT_STATUS,
+                    "The command did not finish within {:?}.", timeout
+                ))
+            }
+        },
+        None => child.wait().await.map_err(Error::from),
+    };
+
+    if let (Some(id), Ok(mut children)) = (id, CHILDREN.lock()) {
+        children.remove(&id);
+    }
+
+    if let Ok(status) = &result {
+        if status.success() {
+            RECEIVED.store(0, Ordering::SeqCst);
+        }
+    }
+
+    result
+}
+
+/// Writes the input of a command to its standard input stream, and then closes the stream.
+async fn write_input(target: Option<impl AsyncWrite + Unpin>, input: Option<&str>) -> Result<()> {
+    if let (Some(mut target), Some(input)) = (target, input) {
+        target.write_all(input.as_bytes()).await?;
+        target.shutdown().await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Seek;
+
+    #[test]
+    fn argument_building() {
+        let args = Run::new("test")
+            .arg("arg1")
+            .arg("arg2")
+            .arg("arg3")
+            .get_args()
+            .to_owned();
+
+        assert_eq!(args.as_ref(), vec!["arg1", "arg2", "arg3"]);
+    }
+
+    #[test]
+    fn aws_options_added() {
         let context = Application::test(Some("profile".to_owned()), Some("region".to_owned()));
 
         let args = Run::new("aws")
@@ -297,7 +1250,11 @@ mod test {
     #[cfg(unix)]
     #[test]
     fn collect_output() {
-        let result = Run::new("printf").arg("Hello, %s!").arg("world").output();
+        let context = Application::test(None, None);
+        let result = Run::new("printf")
+            .arg("Hello, %s!")
+            .arg("world")
+            .output(&context);
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "Hello, world!");
@@ -305,7 +1262,8 @@ mod test {
 
     #[test]
     fn collect_output_not_in_path() {
-        let result = Run::new("does-not-exist").output();
+        let context = Application::test(None, None);
+        let result = Run::new("does-not-exist").output(&context);
 
         assert!(result.is_err());
         assert_eq!(
@@ -314,6 +1272,83 @@ mod test {
         );
     }
 
+    #[test]
+    fn command_display() {
+        let mut run = Run::new("aws");
+
+        run.env("AWS_PROFILE", "my profile")
+            .arg("configure")
+            .arg("set")
+            .arg("aws_secret_access_key")
+            .secret_arg("secret")
+            .arg("--query")
+            .arg("it's")
+            .input("password");
+
+        assert_eq!(
+            run.to_string(),
+            "AWS_PROFILE='my profile' aws configure set aws_secret_access_key '<redacted>' --query 'it'\\''s' <<< '<redacted>'"
+        );
+    }
+
+    #[test]
+    fn dry_run_printed() {
+        let context = Application::test(None, None).with_dry_run(true);
+
+        let result = Run::new("does-not-exist")
+            .arg("login")
+            .input("password")
+            .pass_through(&context);
+
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output.to_string_lossy(),
+            "does-not-exist login <<< '<redacted>'\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dry_run_read_only() {
+        let context = Application::test(None, None).with_dry_run(true);
+
+        let result = Run::new("printf")
+            .arg("Hello, %s!")
+            .arg("world")
+            .read_only()
+            .output(&context);
+
+        assert_eq!(result.unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn json_output() {
+        let context = Application::test(None, None).with_executor(
+            Script::default()
+                .expect("aws", &["eks", "list-clusters", "--output", "json"])
+                .output("{\"clusters\": [\"apps\", \"data\"]}")
+                .expect("aws", &["eks", "list-clusters", "--output", "json"])
+                .output("\n"),
+        );
+
+        let parsed = Run::new("aws")
+            .arg("eks")
+            .arg("list-clusters")
+            .json::<HashMap<String, Vec<String>>>(&context);
+        let invalid = Run::new("aws")
+            .arg("eks")
+            .arg("list-clusters")
+            .json::<HashMap<String, Vec<String>>>(&context);
+
+        assert_eq!(parsed.unwrap()["clusters"], vec!["apps", "data"]);
+        assert!(format!("{}", invalid.unwrap_err())
+            .starts_with("The output of the command is not in the expected format: "));
+    }
+
     #[cfg(unix)]
     #[test]
     fn found_in_path() {
@@ -325,6 +1360,171 @@ mod test {
         assert!(cache.get("printf").unwrap());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn interactive_exit_status() {
+        let context = Application::test(None, None);
+        let result = Run::new("sh")
+            .arg("-c")
+            .arg("test \"$AWS_PROFILE\" = profile && exit 3")
+            .env("AWS_PROFILE", "profile")
+            .interactive(&context);
+
+        assert!(format!("{:?}", result.unwrap_err()).contains("status: 3"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn interactive_not_timed_out() {
+        let context = Application::test(None, None).with_timeout(1);
+
+        assert!(Run::new("sleep").arg("2").interactive(&context).is_ok());
+
+        let result = Run::new("sleep")
+            .arg("10")
+            .timeout(time::Duration::from_secs(1))
+            .interactive(&context);
+
+        assert!(format!("{:?}", result.unwrap_err()).contains("status: 124"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_forwarded() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut children = [
+            process::Command::new("sleep").arg("10").spawn().unwrap(),
+            process::Command::new("sleep").arg("10").spawn().unwrap(),
+        ];
+
+        forward(
+            libc::SIGTERM,
+            &children.iter().map(|child| child.id()).collect(),
+        );
+
+        for child in &mut children {
+            assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_stops_interactive() {
+        // The signal is sent to the whole test process, so it is received in a process of its own
+        // to keep it from stopping the commands run by the other tests.
+        if std::env::var("AWS_LOGIN_TEST_SIGNAL").is_err() {
+            let output = process::Command::new(std::env::current_exe().unwrap())
+                .arg("util::run::test::signal_stops_interactive")
+                .arg("--exact")
+                .env("AWS_LOGIN_TEST_SIGNAL", "1")
+                .output()
+                .unwrap();
+
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stdout)
+            );
+
+            return;
+        }
+
+        let context = Application::test(None, None);
+        let started = std::time::Instant::now();
+
+        // The command waits for the application to start listening for signals before it asks for
+        // the application to be stopped.
+        let result = Run::new("sh")
+            .arg("-c")
+            .arg("sleep 1; kill -TERM $PPID; exec sleep 10")
+            .interactive(&context);
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(format!("{:?}", result.unwrap_err()).contains("status: 143"));
+        assert!(Run::new("true").output(&context).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_exit_status() {
+        let context = Application::test(None, None);
+        let result = Run::new("sh")
+            .arg("-c")
+            .arg("kill -TERM $$")
+            .pass_through(&context);
+
+        assert!(format!("{:?}", result.unwrap_err()).contains("status: 143"));
+
+        let result = Run::new("sh")
+            .arg("-c")
+            .arg("trap 'exit 0' TERM; kill -TERM $$")
+            .pass_through(&context);
+
+        assert!(result.is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn input_written() {
+        let context = Application::test(None, None);
+        let result = Run::new("sh")
+            .arg("-c")
+            .arg("read -r password && printf '%s' \"$password\" | wc -c")
+            .input("secret\n")
+            .output(&context);
+
+        assert_eq!(result.unwrap().trim(), "6");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_out() {
+        let context = Application::test(None, None).with_timeout(1);
+        let started = std::time::Instant::now();
+        let result = Run::new("sleep").arg("10").output(&context);
+        let error = result.unwrap_err();
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(format!("{:?}", error).contains("status: 124"));
+        assert_eq!(
+            format!("{}", error),
+            "The command did not finish within 1s.\n"
+        );
+    }
+
+    #[test]
+    fn traced_command() {
+        let context = Application::test(None, None).with_verbose(2).with_executor(
+            Script::default()
+                .expect("aws", &["configure", "set", "aws_session_token", "token"])
+                .status(3),
+        );
+
+        let result = Run::new("aws")
+            .arg("configure")
+            .arg("set")
+            .arg("aws_session_token")
+            .secret_arg("token")
+            .pass_through(&context);
+
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        let trace = error.to_string_lossy();
+        let lines = trace.lines().collect::<Vec<&str>>();
+
+        assert!(result.is_err());
+        assert_eq!(
+            lines[0],
+            "aws-login: Running: aws configure set aws_session_token '<redacted>'"
+        );
+        assert!(lines[1].starts_with(
+            "aws-login: aws configure set aws_session_token '<redacted>' (exit status 3, "
+        ));
+    }
+
     #[test]
     fn not_found_in_path() {
         assert!(!in_path("does-not-exist").unwrap());
@@ -353,6 +1553,57 @@ mod test {
         assert_eq!(output.to_string_lossy(), "Hello, world!");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn pass_through_large_output() {
+        let path = std::env::temp_dir().join(format!("aws-login-run-{}", std::process::id()));
+        let mut expected = String::new();
+
+        for i in 0..100_000 {
+            expected.push_str(&format!(
+                "{:>10} The quick brown fox jumps over the lazy dog.\n",
+                i
+            ));
+        }
+
+        std::fs::write(&path, &expected).unwrap();
+
+        let context = Application::test(None, None);
+        let result = Run::new("cat")
+            .arg(&path.to_string_lossy())
+            .pass_through(&context);
+
+        std::fs::remove_file(&path).unwrap();
+
+        let mut output = context.output();
+
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(output.to_string_lossy(), expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pass_through_output_and_error() {
+        let context = Application::test(None, None);
+
+        let result = Run::new("sh")
+            .arg("-c")
+            .arg("printf 'out\\n'; printf 'err\\n' >&2; printf 'Prompt: '")
+            .pass_through(&context);
+
+        let mut error = context.error();
+        let mut output = context.output();
+
+        error.rewind().unwrap();
+        output.rewind().unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(error.to_string_lossy(), "err\n");
+        assert_eq!(output.to_string_lossy(), "out\nPrompt: ");
+    }
+
     #[test]
     fn pass_through_output_not_in_path() {
         let context = Application::test(None, None);
@@ -364,4 +1615,60 @@ mod test {
             "The program, does-not-exist, could be found in PATH.\n"
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn parallel_output() {
+        let context = Application::test(None, None);
+        let runs = ["first", "second", "third"].map(|word| {
+            let mut run = Run::new("sh");
+
+            run.arg("-c").arg(&format!("sleep 0.5; printf {}", word));
+            run
+        });
+
+        let started = std::time::Instant::now();
+        let [first, second, third] = Run::output_all(&context, runs);
+
+        assert!(started.elapsed() < std::time::Duration::from_millis(1400));
+        assert_eq!(first.unwrap(), "first");
+        assert_eq!(second.unwrap(), "second");
+        assert_eq!(third.unwrap(), "third");
+    }
+
+    #[test]
+    fn scripted_commands() {
+        let context = Application::test(None, None).with_executor(
+            Script::default()
+                .expect("aws", &["configure", "list-profiles"])
+                .output("dev\n")
+                .expect("aws", &["sso", "login"])
+                .error("Expired.\n")
+                .status(255),
+        );
+
+        let output = Run::new("aws")
+            .arg("configure")
+            .arg("list-profiles")
+            .output(&context);
+        let result = Run::new("aws")
+            .arg("sso")
+            .arg("login")
+            .pass_through(&context);
+
+        let mut error = context.error();
+
+        error.rewind().unwrap();
+
+        assert_eq!(output.unwrap(), "dev\n");
+        assert!(format!("{:?}", result.unwrap_err()).contains("status: 255"));
+        assert_eq!(error.to_string_lossy(), "Expired.\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "The expected commands were not run")]
+    fn scripted_commands_not_run() {
+        Application::test(None, None)
+            .with_executor(Script::default().expect("aws", &["configure", "list-profiles"]));
+    }
 }
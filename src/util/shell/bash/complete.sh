@@ -0,0 +1,33 @@
+
+###
+# Completes dynamic values for the AWS Login command.
+#
+# This function will complete the names of profiles and EKS clusters by asking
+# the application for them. Everything else is completed by the function
+# generated from the command line interface.
+##
+_aws_login_dynamic()
+{
+    local CUR="${COMP_WORDS[COMP_CWORD]}"
+    local PREV="${COMP_WORDS[COMP_CWORD-1]}"
+    local KIND=
+
+    if [ "$PREV" = "--profile" ]; then
+        KIND=profiles
+    elif [ "${COMP_WORDS[1]}" = "eks" ] && [ "$COMP_CWORD" -eq 2 ] && [[ "$CUR" != -* ]]; then
+        KIND=clusters
+    fi
+
+    if [ -n "$KIND" ]; then
+        local IFS=$'\n'
+
+        # shellcheck disable=SC2207
+        COMPREPLY=($(compgen -W "$({AWS_LOGIN} complete "$KIND" 2> /dev/null)" -- "$CUR"))
+
+        return 0
+    fi
+
+    _aws-login "$@"
+}
+
+complete -F _aws_login_dynamic -o bashdefault -o default aws-login
@@ -4,7 +4,7 @@
 # Integrates the AWS Login command with the shell.
 #
 # This function will be invoked instead of the command whenever you type
-# `{AWS_LOGIN}` and is responsible for evaluating any shell code it writes
+# `aws-login` and is responsible for evaluating any shell code it writes
 # to a file.
 #
 # shellcheck disable=SC2288
@@ -19,7 +19,7 @@ aws-login()
     if AWS_LOGIN_SCRIPT="$(mktemp)"; then
 
         # Execute the real command.
-        AWS_LOGIN_SCRIPT="$AWS_LOGIN_SCRIPT" {AWS_LOGIN_SHELL}=bash "{AWS_LOGIN}" "$@"
+        AWS_LOGIN_SCRIPT="$AWS_LOGIN_SCRIPT" {AWS_LOGIN_SHELL}=bash {AWS_LOGIN} "$@"
 
         local STATUS=$?
 
@@ -35,3 +35,27 @@ aws-login()
 
     return 1
 }
+
+###
+# Switches the AWS CLI profile when entering or leaving a project.
+#
+# This function will ask `aws-login` to look for a project file whenever
+# the current directory changes, which will make the profile named in it the
+# active AWS CLI profile until the project is left.
+##
+_aws_login_hook()
+{
+    local STATUS=$?
+
+    if [ "$_AWS_LOGIN_PWD" != "$PWD" ]; then
+        _AWS_LOGIN_PWD="$PWD"
+
+        aws-login hook
+    fi
+
+    return $STATUS
+}
+
+if [[ ";${PROMPT_COMMAND:-};" != *";_aws_login_hook;"* ]]; then
+    PROMPT_COMMAND="_aws_login_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+fi
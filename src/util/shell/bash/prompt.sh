@@ -0,0 +1,11 @@
+###
+# Adds the AWS Login segment to the shell prompt.
+#
+# The segment shows the active AWS CLI profile, the region, and the time left
+# before the SSO session expires. It is only added once.
+##
+_AWS_LOGIN_BIN={AWS_LOGIN}
+
+if [[ "$PS1" != *" prompt --shell bash "* ]]; then
+    PS1='$("$_AWS_LOGIN_BIN" prompt --shell bash --format "[{profile}[@{region}][ ({expiry})] ]")'"$PS1"
+fi
@@ -0,0 +1,414 @@
+//! Manages the block of shell code installed into a profile's startup script.
+//!
+//! The integration is installed as a block of shell code surrounded by a pair of comments that
+//! mark where it begins and ends. The beginning comment also records the version of the block
+//! format so that outdated blocks, or blocks that refer to a different location for the binary,
+//! can be detected and replaced in place without affecting the rest of the startup script.
+//!
+//! ```text
+//! # BEGIN aws-login (version 1)
+//! eval "$('/usr/local/bin/aws-login' shell init -s bash)"
+//! # END aws-login
+//! ```
+
+use carli::err;
+use carli::error::{Context, Error, Result};
+use std::{fs, path};
+
+/// The extension appended to the name of a startup script to create its backup.
+const BACKUP_EXTENSION: &str = "aws-login.bak";
+
+/// The comment marking the beginning of the block, without the version.
+const BEGIN_COMMENT: &str = "# BEGIN aws-login";
+
+/// The comment marking the end of the block.
+const END_COMMENT: &str = "# END aws-login";
+
+/// The comment used by older releases to mark the installed integration.
+///
+/// Before the block had beginning and ending comments, this comment was followed by exactly one
+/// line of shell code. Blocks using this comment are treated as outdated and are replaced when the
+/// integration is installed again.
+const LEGACY_COMMENT: &str = "# Integrate aws-login into the shell environment.";
+
+/// The current version of the block format.
+const VERSION: u32 = 1;
+
+/// A block that was found in the contents of a startup script.
+struct Found {
+    /// The shell code between the beginning and ending comments.
+    body: Vec<String>,
+
+    /// The index of the line after the last line of the block.
+    end: usize,
+
+    /// The index of the first line of the block.
+    start: usize,
+
+    /// The version of the block format, if one was recorded.
+    version: Option<u32>,
+}
+
+/// Finds the installed block in the lines of a startup script.
+///
+/// If no block could be found, [`None`] is returned. If the beginning of a block was found but not
+/// its end, an error is returned instead of guessing where the block ends, since the lines after it
+/// could belong to the user.
+fn find(lines: &[&str]) -> Result<Option<Found>> {
+    for (index, line) in lines.iter().enumerate() {
+        let line = line.trim_end();
+
+        if let Some(rest) = line.strip_prefix(BEGIN_COMMENT) {
+            let end = match lines[index..]
+                .iter()
+                .position(|l| l.trim_end() == END_COMMENT)
+            {
+                Some(position) => index + position,
+                None => err!(
+                    1,
+                    "The integration block on line {} is missing its ending comment ({}). Please remove the block, or add the comment after it, and try again.",
+                    index + 1,
+                    END_COMMENT
+                ),
+            };
+
+            let version = rest
+                .trim()
+                .strip_prefix("(version ")
+                .and_then(|v| v.strip_suffix(')'))
+                .and_then(|v| v.parse::<u32>().ok());
+
+            return Ok(Some(Found {
+                body: lines[index + 1..end]
+                    .iter()
+                    .map(|l| l.trim_end().to_owned())
+                    .collect(),
+                end: end + 1,
+                start: index,
+                version,
+            }));
+        } else if line == LEGACY_COMMENT {
+            return Ok(Some(Found {
+                body: lines
+                    .get(index + 1)
+                    .map(|l| vec![l.trim_end().to_owned()])
+                    .unwrap_or_default(),
+                end: lines.len().min(index + 2),
+                start: index,
+                version: None,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Generates a new block containing the given shell code.
+fn generate(code: &str) -> String {
+    format!(
+        "{} (version {})\n{}\n{}\n",
+        BEGIN_COMMENT, VERSION, code, END_COMMENT
+    )
+}
+
+/// Installs a block containing the shell code into the startup script.
+///
+/// If the startup script does not exist, it is created along with any missing directories. If a
+/// block is already installed, it is replaced in place after a backup of the startup script has
+/// been made. Otherwise, the block is appended to the end of the startup script, separated from
+/// any existing contents by an empty line.
+pub fn install(script: &path::Path, code: &str) -> Result<()> {
+    let contents = if script.exists() {
+        read(script)?
+    } else {
+        if let Some(parent_dir) = script.parent() {
+            if !parent_dir.as_os_str().is_empty() && !parent_dir.exists() {
+                fs::create_dir_all(parent_dir)
+                    .map_err(Error::from)
+                    .context(|| {
+                        "Could not create the directory containing the profile script.".to_owned()
+                    })?;
+            }
+        }
+
+        String::new()
+    };
+
+    let mut lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+    let block = generate(code);
+
+    let modified = match find(&lines).context(|| unterminated(script))? {
+        Some(found) => {
+            backup(script)?;
+
+            lines.splice(found.start..found.end, [block.as_str()]);
+            lines.concat()
+        }
+        None => {
+            let mut modified = contents.clone();
+
+            if !modified.is_empty() {
+                if !modified.ends_with('\n') {
+                    modified.push('\n');
+                }
+
+                modified.push('\n');
+            }
+
+            modified.push_str(&block);
+            modified
+        }
+    };
+
+    write(script, &modified)
+}
+
+/// Checks if the installed block is current and contains the given shell code.
+///
+/// A block is not current if it was installed using an older version of the block format, or if
+/// its shell code differs (e.g. the application binary was moved to a different location).
+pub fn is_current(script: &path::Path, code: &str) -> Result<bool> {
+    if !script.exists() {
+        return Ok(false);
+    }
+
+    let contents = read(script)?;
+    let lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+
+    Ok(match find(&lines).context(|| unterminated(script))? {
+        Some(found) => found.version == Some(VERSION) && found.body.join("\n") == code,
+        None => false,
+    })
+}
+
+/// Checks if a block, current or not, is installed in the startup script.
+pub fn is_installed(script: &path::Path) -> Result<bool> {
+    if !script.exists() {
+        return Ok(false);
+    }
+
+    let contents = read(script)?;
+    let lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+
+    Ok(find(&lines).context(|| unterminated(script))?.is_some())
+}
+
+/// Removes the installed block from the startup script after making a backup of it.
+///
+/// The empty line that is added before the block during installation is also removed. The backup
+/// is created alongside the startup script using the same file name with the [`BACKUP_EXTENSION`]
+/// appended to it. The path to the backup is returned.
+pub fn uninstall(script: &path::Path) -> Result<path::PathBuf> {
+    let contents = read(script)?;
+    let mut lines = contents.split_inclusive('\n').collect::<Vec<&str>>();
+
+    let found = match find(&lines).context(|| unterminated(script))? {
+        Some(found) => found,
+        None => err!(1, "The integration script could not be found."),
+    };
+
+    let mut start = found.start;
+
+    if start > 0 && lines[start - 1].trim().is_empty() {
+        start -= 1;
+    }
+
+    lines.drain(start..found.end);
+
+    let backup = backup(script)?;
+
+    write(script, &lines.concat())?;
+
+    Ok(backup)
+}
+
+/// Makes a backup copy of the startup script and returns its path.
+///
+/// An existing backup is never replaced. If one already exists, a number is added to the name of
+/// the new backup (e.g. `.bashrc.aws-login.1.bak`).
+fn backup(script: &path::Path) -> Result<path::PathBuf> {
+    let name = |number: u32| {
+        let mut backup = script.as_os_str().to_owned();
+
+        backup.push(".");
+
+        if number > 0 {
+            backup.push(format!("aws-login.{}.bak", number));
+        } else {
+            backup.push(BACKUP_EXTENSION);
+        }
+
+        path::PathBuf::from(backup)
+    };
+
+    let backup = match (0..).map(name).find(|backup| !backup.exists()) {
+        Some(backup) => backup,
+        None => err!(1, "A name could not be found for the backup."),
+    };
+
+    fs::copy(script, &backup)
+        .map_err(Error::from)
+        .context(|| format!("Could not create a backup: {}", backup.display()))?;
+
+    Ok(backup)
+}
+
+/// Reads the contents of the startup script.
+fn read(script: &path::Path) -> Result<String> {
+    fs::read_to_string(script)
+        .map_err(Error::from)
+        .context(|| format!("Could not read the startup script: {}", script.display()))
+}
+
+/// Returns the context used when the installed block in the startup script is not terminated.
+fn unterminated(script: &path::Path) -> String {
+    format!(
+        "The integration block in {} is not terminated.",
+        script.display()
+    )
+}
+
+/// Writes the contents of the startup script.
+fn write(script: &path::Path, contents: &str) -> Result<()> {
+    fs::write(script, contents)
+        .map_err(Error::from)
+        .context(|| format!("Could not write the startup script: {}", script.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Splits the contents into lines the same way the module does.
+    fn lines(contents: &str) -> Vec<&str> {
+        contents.split_inclusive('\n').collect()
+    }
+
+    #[test]
+    fn find_current_block() {
+        let contents = format!("export A=1\n\n{}export B=2\n", generate("code"));
+        let lines = lines(&contents);
+        let found = find(&lines).unwrap().unwrap();
+
+        assert_eq!(found.body, vec!["code"]);
+        assert_eq!(found.version, Some(VERSION));
+        assert_eq!(lines[..found.start].concat(), "export A=1\n\n");
+        assert_eq!(lines[found.end..].concat(), "export B=2\n");
+    }
+
+    #[test]
+    fn find_legacy_block() {
+        let contents = format!(
+            "export A=1\n\n{}\neval \"$(aws-login shell init -s bash)\"\nexport B=2\n",
+            LEGACY_COMMENT
+        );
+
+        let lines = lines(&contents);
+        let found = find(&lines).unwrap().unwrap();
+
+        assert_eq!(found.body, vec!["eval \"$(aws-login shell init -s bash)\""]);
+        assert_eq!(found.version, None);
+        assert_eq!(lines[..found.start].concat(), "export A=1\n\n");
+        assert_eq!(lines[found.end..].concat(), "export B=2\n");
+    }
+
+    #[test]
+    fn find_missing_block() {
+        assert!(find(&lines("export A=1\n")).unwrap().is_none());
+    }
+
+    #[test]
+    fn find_unterminated_block() {
+        let contents = format!("export A=1\n{} (version 1)\ncode\n", BEGIN_COMMENT);
+
+        assert_eq!(
+            format!("{}", find(&lines(&contents)).err().unwrap()),
+            "The integration block on line 2 is missing its ending comment (# END aws-login). Please remove the block, or add the comment after it, and try again.\n"
+        );
+    }
+
+    #[test]
+    fn install_and_uninstall() {
+        let dir = std::env::temp_dir().join(format!("aws-login-block-{}", std::process::id()));
+        let script = dir.join("nested").join(".bashrc");
+
+        install(&script, "old").unwrap();
+
+        assert!(is_installed(&script).unwrap());
+        assert!(is_current(&script, "old").unwrap());
+        assert!(!is_current(&script, "new").unwrap());
+
+        let mut contents = fs::read_to_string(&script).unwrap();
+
+        contents.insert_str(0, "export A=1\n\n");
+        contents.push_str("export B=2\n");
+
+        fs::write(&script, &contents).unwrap();
+
+        install(&script, "new").unwrap();
+
+        assert!(is_current(&script, "new").unwrap());
+        assert_eq!(
+            fs::read_to_string(&script).unwrap(),
+            format!("export A=1\n\n{}export B=2\n", generate("new"))
+        );
+
+        let backup = uninstall(&script).unwrap();
+
+        assert!(!is_installed(&script).unwrap());
+        assert_eq!(
+            fs::read_to_string(&script).unwrap(),
+            "export A=1\nexport B=2\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&backup).unwrap(),
+            format!("export A=1\n\n{}export B=2\n", generate("new"))
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn install_over_unterminated_block() {
+        let dir = std::env::temp_dir().join(format!("aws-login-broken-{}", std::process::id()));
+        let script = dir.join(".bashrc");
+        let contents = format!("{} (version 1)\ncode\nexport A=1\n", BEGIN_COMMENT);
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&script, &contents).unwrap();
+
+        assert_eq!(
+            format!("{}", install(&script, "new").unwrap_err()),
+            format!(
+                "The integration block in {} is not terminated.\n  The integration block on line 1 is missing its ending comment (# END aws-login). Please remove the block, or add the comment after it, and try again.\n",
+                script.display()
+            )
+        );
+        assert!(uninstall(&script).is_err());
+        assert_eq!(fs::read_to_string(&script).unwrap(), contents);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keep_previous_backups() {
+        let dir = std::env::temp_dir().join(format!("aws-login-backup-{}", std::process::id()));
+        let script = dir.join(".bashrc");
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&script, "first\n").unwrap();
+
+        let first = backup(&script).unwrap();
+
+        fs::write(&script, "second\n").unwrap();
+
+        let second = backup(&script).unwrap();
+
+        assert_eq!(first, dir.join(".bashrc.aws-login.bak"));
+        assert_eq!(second, dir.join(".bashrc.aws-login.1.bak"));
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first\n");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
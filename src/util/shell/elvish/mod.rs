@@ -0,0 +1,128 @@
+//! Provides support for integrating into Elvish.
+//!
+//! This support module will allow the application to generate a shell script that is evaluated
+//! once the application has exited. The location of the script will depend on the value of the
+//! `AWS_LOGIN_SCRIPT` environment variable.
+
+use super::{block, quote};
+use crate::util::config::BIN_NAME;
+use carli::error::{Context, Error, Result};
+use std::io::Write;
+use std::path;
+
+/// Manages the current Elvish environment.
+pub struct Environment<'a> {
+    /// The destination of the shell code that will be evaluated.
+    file: Box<dyn Write + 'a>,
+}
+
+impl<'a> Environment<'a> {
+    /// Creates a new instance of [`Environment`] that writes to the given destination.
+    pub fn new(file: Box<dyn Write + 'a>) -> Self {
+        Self { file }
+    }
+}
+
+impl super::Environment for Environment<'_> {
+    fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
+        writeln!(
+            self.file,
+            "set-env {} {}",
+            quote::name(name)?,
+            quote::elvish(value)
+        )
+        .map_err(Error::from)
+        .context(|| "Could not set environment variable.".to_owned())
+    }
+
+    fn unset_var(&mut self, name: &str) -> Result<()> {
+        writeln!(self.file, "unset-env {}", quote::name(name)?)
+            .map_err(Error::from)
+            .context(|| "Could not unset environment variable.".to_owned())
+    }
+}
+
+/// Manages the integration of the application into an Elvish environment.
+pub struct Setup {
+    /// The path to the profile startup script.
+    script: path::PathBuf,
+}
+
+impl Setup {
+    /// Creates a new instance of [`Setup`] for managing Elvish integration.
+    pub fn new(profile: Option<&str>) -> Self {
+        let script = profile
+            .map(path::PathBuf::from)
+            .unwrap_or_else(get_default_profile);
+
+        Self { script }
+    }
+
+    /// Generates the shell code installed into the startup script.
+    ///
+    /// Functions defined by `eval` are only visible inside of its own namespace, so the function
+    /// is added to the interactive namespace once the initialization script has been evaluated.
+    fn generate_code(&self) -> String {
+        format!(
+            "eval ({} shell init -s elvish | slurp) &on-end={{|ns| edit:add-var aws-login~ $ns[aws-login~] }}",
+            quote::elvish(&BIN_NAME)
+        )
+    }
+}
+
+impl super::Setup for Setup {
+    fn generate_prompt(&self) -> Option<String> {
+        None
+    }
+
+    fn generate_script(&self) -> String {
+        include_str!("init.elv")
+            .replace("{AWS_LOGIN}", &quote::elvish(&BIN_NAME))
+            .replace("{AWS_LOGIN_SHELL}", super::SHELL_NAME)
+    }
+
+    fn install(&self) -> Result<()> {
+        block::install(&self.script, &self.generate_code())
+    }
+
+    fn is_current(&self) -> Result<bool> {
+        block::is_current(&self.script, &self.generate_code())
+    }
+
+    fn is_installed(&self) -> Result<bool> {
+        block::is_installed(&self.script)
+    }
+
+    fn script(&self) -> &path::Path {
+        &self.script
+    }
+
+    fn uninstall(&self) -> Result<path::PathBuf> {
+        block::uninstall(&self.script)
+    }
+}
+
+/// Generates the path to the default profile script location.
+///
+/// Older releases of Elvish read `~/.elvish/rc.elv`, which is used if it exists and the current
+/// location does not.
+fn get_default_profile() -> path::PathBuf {
+    let home = home::home_dir().expect("The home directory could not be determined.");
+
+    if cfg!(windows) {
+        return home
+            .join("AppData")
+            .join("Roaming")
+            .join("elvish")
+            .join("rc.elv");
+    }
+
+    let rc = home.join(".config").join("elvish").join("rc.elv");
+    let legacy = home.join(".elvish").join("rc.elv");
+
+    if !rc.exists() && legacy.exists() {
+        legacy
+    } else {
+        rc
+    }
+}
@@ -0,0 +1,12 @@
+
+###
+# Completes dynamic values for the AWS Login command.
+#
+# These completions will complete the names of profiles and EKS clusters by
+# asking the application for them. They are added to the ones generated from
+# the command line interface.
+##
+set -g __aws_login_bin {AWS_LOGIN}
+
+complete -c aws-login -l profile -x -a '($__aws_login_bin complete profiles 2> /dev/null)'
+complete -c aws-login -n '__fish_seen_subcommand_from eks' -f -a '($__aws_login_bin complete clusters 2> /dev/null)'
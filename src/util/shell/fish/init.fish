@@ -2,27 +2,40 @@
 # Integrates the AWS Login command with the shell.
 #
 # This function will be invoked instead of the command whenever you type
-# `{AWS_LOGIN}` and is responsible for evaluating any shell code it writes
+# `aws-login` and is responsible for evaluating any shell code it writes
 # to a file.
 ##
 function aws-login
     # Create the shell script file.
-    if set -x AWS_LOGIN_SCRIPT (mktemp)
+    if set -l AWS_LOGIN_SCRIPT (mktemp)
 
         # Execute the real command.
-        {AWS_LOGIN_SHELL}=bash "{AWS_LOGIN}" $argv
+        env AWS_LOGIN_SCRIPT="$AWS_LOGIN_SCRIPT" {AWS_LOGIN_SHELL}=fish {AWS_LOGIN} $argv
 
-        set STATUS $status
+        set -l STATUS $status
 
         # Evaluate the shell script if it is not empty.
         if test -s "$AWS_LOGIN_SCRIPT"
             cat "$AWS_LOGIN_SCRIPT" | source
-
-            rm "$AWS_LOGIN_SCRIPT"
-
-            return $STATUS
         end
 
-        return 1
+        rm "$AWS_LOGIN_SCRIPT"
+
+        return $STATUS
     end
+
+    return 1
+end
+
+###
+# Switches the AWS CLI profile when entering or leaving a project.
+#
+# This function will ask `aws-login` to look for a project file whenever
+# the current directory changes, which will make the profile named in it the
+# active AWS CLI profile until the project is left.
+##
+function __aws_login_hook --on-variable PWD
+    aws-login hook
 end
+
+__aws_login_hook
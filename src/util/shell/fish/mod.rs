@@ -4,52 +4,41 @@
 //! once the application has exited. The location of the script will depend on the value of the
 //! `AWS_LOGIN_SCRIPT` environment variable.
 
+use super::{block, quote};
 use crate::util::config::BIN_NAME;
 use carli::error::{Context, Error, Result};
 use std::io::Write;
-use std::{env, fs, path};
-
-/// The comment used to check if the integration script is installed.
-///
-/// The presence of this comment in the profile startup script will inform the application that
-/// the integration script has already been installed. If the integration needs to be re-done,
-/// the user must undo the integration.
-const INSTALLED_COMMENT: &str = "# Integrate aws-login into the shell environment.";
-
-/// The name of the environment variable used to specify the shell script path.
-///
-/// The file path defined in this environment variable will be created if it does not already
-/// exist, and then appended to as changes are specified for the environment. Once the utility
-/// exits, the parent process is expected to evaluate and then clean up the file.
-const SCRIPT_PATH: &str = "AWS_LOGIN_SCRIPT";
+use std::path;
 
 /// Manages the current Fish environment.
-pub struct Environment {
-    /// The file that will be used to evaluate shell code.
-    file: fs::File,
+pub struct Environment<'a> {
+    /// The destination of the shell code that will be evaluated.
+    file: Box<dyn Write + 'a>,
 }
 
-impl super::Environment for Environment {
-    fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
-        write!(self.file, "set -gx {} \"{}\"", name, value)
-            .map_err(Error::from)
-            .context(|| "Could not set environment variable.".to_owned())
+impl<'a> Environment<'a> {
+    /// Creates a new instance of [`Environment`] that writes to the given destination.
+    pub fn new(file: Box<dyn Write + 'a>) -> Self {
+        Self { file }
     }
 }
 
-impl Default for Environment {
-    fn default() -> Self {
-        let path = path::PathBuf::from(
-            env::var(SCRIPT_PATH).expect("Unable to determine where to write the shell script to."),
-        );
-
-        let file = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
-            .unwrap_or_else(|_| panic!("{}: Unable to open the file for writing.", path.display()));
+impl super::Environment for Environment<'_> {
+    fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
+        writeln!(
+            self.file,
+            "set -gx {} {}",
+            quote::name(name)?,
+            quote::fish(value)
+        )
+        .map_err(Error::from)
+        .context(|| "Could not set environment variable.".to_owned())
+    }
 
-        Self { file }
+    fn unset_var(&mut self, name: &str) -> Result<()> {
+        writeln!(self.file, "set -e {}", quote::name(name)?)
+            .map_err(Error::from)
+            .context(|| "Could not unset environment variable.".to_owned())
     }
 }
 
@@ -68,49 +57,49 @@ impl Setup {
 
         Self { script }
     }
+
+    /// Generates the shell code installed into the startup script.
+    fn generate_code(&self) -> String {
+        format!("{} shell init -s fish | source", quote::fish(&BIN_NAME))
+    }
 }
 
 impl super::Setup for Setup {
+    fn generate_prompt(&self) -> Option<String> {
+        Some(include_str!("prompt.fish").replace("{AWS_LOGIN}", &quote::fish(&BIN_NAME)))
+    }
+
     fn generate_script(&self) -> String {
-        include_str!("init.fish")
-            .replace("{AWS_LOGIN}", &BIN_NAME)
+        let script = format!(
+            "{}\n{}{}",
+            include_str!("init.fish"),
+            generate_completions(),
+            include_str!("complete.fish")
+        );
+
+        script
+            .replace("{AWS_LOGIN}", &quote::fish(&BIN_NAME))
             .replace("{AWS_LOGIN_SHELL}", super::SHELL_NAME)
     }
 
     fn install(&self) -> Result<()> {
-        let parent_dir = self.script.parent().unwrap();
-
-        if !parent_dir.exists() {
-            fs::create_dir_all(parent_dir)
-                .map_err(|error| {
-                    Error::new(error.raw_os_error().unwrap_or(1)).message(format!("{}", error))
-                })
-                .context(|| {
-                    "Could not create the directory containing the profile script.".to_owned()
-                })?;
-        }
-
-        let mut handle = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.script)?;
-
-        writeln!(handle, "\n{}", INSTALLED_COMMENT)?;
-        writeln!(handle, "{} shell init -s fish | source", *BIN_NAME)?;
-
-        Ok(())
+        block::install(&self.script, &self.generate_code())
+    }
+
+    fn is_current(&self) -> Result<bool> {
+        block::is_current(&self.script, &self.generate_code())
     }
 
     fn is_installed(&self) -> Result<bool> {
-        if self.script.exists() {
-            let contents = fs::read_to_string(&self.script)?;
+        block::is_installed(&self.script)
+    }
 
-            if contents.contains(INSTALLED_COMMENT) {
-                return Ok(true);
-            }
-        }
+    fn script(&self) -> &path::Path {
+        &self.script
+    }
 
-        Ok(false)
+    fn uninstall(&self) -> Result<path::PathBuf> {
+        block::uninstall(&self.script)
     }
 }
 
@@ -122,3 +111,8 @@ fn get_default_profile() -> path::PathBuf {
         .join("fish")
         .join("config.fish")
 }
+
+/// Generates the completions for the command line interface.
+fn generate_completions() -> String {
+    super::generate_completions(clap_complete::shells::Fish)
+}
@@ -0,0 +1,23 @@
+###
+# Adds the AWS Login segment to the shell prompt.
+#
+# The segment shows the active AWS CLI profile, the region, and the time left
+# before the SSO session expires. It is only added once.
+##
+if not functions -q __aws_login_fish_prompt
+    functions -c fish_prompt __aws_login_fish_prompt
+
+    function __aws_login_status
+        return $argv[1]
+    end
+
+    function fish_prompt
+        set -l STATUS $status
+
+        {AWS_LOGIN} prompt --format '[{profile}[@{region}][ ({expiry})] ]'
+
+        # Restore the status for the original prompt.
+        __aws_login_status $STATUS
+        __aws_login_fish_prompt
+    end
+end
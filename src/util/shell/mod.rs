@@ -1,19 +1,61 @@
 //! Provides support for evaluating shell code.
 
 mod bash;
+mod block;
+mod elvish;
 mod fish;
+mod nu;
 mod powershell;
+pub mod quote;
+mod tcsh;
+mod xonsh;
 mod zsh;
 
+use crate::app::Application;
 use carli::error::Result;
-use std::env;
+use carli::io::Shared;
+use std::io::Write;
+use std::{env, fs, io, path};
+
+/// The name of the environment variable used to specify the shell script path.
+///
+/// The file path defined in this environment variable will be created if it does not already
+/// exist, and then appended to as changes are specified for the environment. Once the utility
+/// exits, the parent process is expected to evaluate and then clean up the file.
+pub const SCRIPT_PATH: &str = "AWS_LOGIN_SCRIPT";
 
 /// The name of the environment variable used to specify the shell name.
 ///
 /// This environment variable is expected to be defined when working with the shell environment.
 /// The initialization script that is used to integrate the application is required to define the
 /// variable using the same name that was used to install it.
-const SHELL_NAME: &str = "AWS_LOGIN_SHELL";
+pub const SHELL_NAME: &str = "AWS_LOGIN_SHELL";
+
+/// The names of the shells that are supported by [`get_setup`].
+pub const SHELLS: &[&str] = &[
+    "bash",
+    "elvish",
+    "fish",
+    "nu",
+    "powershell",
+    "tcsh",
+    "xonsh",
+    "zsh",
+];
+
+/// The name of the environment variable used to mark a subshell started for an AWS CLI profile.
+///
+/// The value is the name of the AWS CLI profile the subshell was started for, which can be used to
+/// show it in the shell prompt. It also keeps project files from switching the profile in the
+/// subshell.
+pub const SUBSHELL_PROFILE: &str = "AWS_LOGIN_SUBSHELL";
+
+/// The name of the environment variable used to track variables set by the application.
+///
+/// Each variable set using [`set_tracked_var`] has its name recorded in this environment variable
+/// as a space separated list. This allows the application to later remove every variable that it
+/// has set in the current shell session, without affecting variables set by the user.
+const TRACKED_VARS: &str = "AWS_LOGIN_VARS";
 
 /// Implemented by types that modify or interact with the current shell environment.
 pub trait Environment {
@@ -27,33 +69,142 @@ pub trait Environment {
     /// env.set_var("EXAMPLE", "Hello, world!")?;
     /// ```
     fn set_var(&mut self, name: &str, value: &str) -> Result<()>;
+
+    /// Removes an environment variable.
+    ///
+    /// ```
+    /// use crate::util::shell;
+    ///
+    /// let mut env = shell::get_env();
+    ///
+    /// env.unset_var("EXAMPLE")?;
+    /// ```
+    fn unset_var(&mut self, name: &str) -> Result<()>;
 }
 
 /// Implemented by types that integrate the application into the current shell environment.
 pub trait Setup {
+    /// Generates the shell code used to add the prompt segment to the shell prompt.
+    ///
+    /// If the shell is not supported, [`None`] is returned.
+    fn generate_prompt(&self) -> Option<String>;
+
     /// Generates the shell code used by a startup script to integrate the application.
     fn generate_script(&self) -> String;
 
     /// Modifies the profile's startup script to integration the application.
+    ///
+    /// If the integration script is already installed, it is replaced with the current version.
     fn install(&self) -> Result<()>;
 
+    /// Checks if the installed integration script is up to date.
+    ///
+    /// The integration script is not up to date if it was installed by an older version of the
+    /// application, or if it refers to a different location for the application binary.
+    fn is_current(&self) -> Result<bool>;
+
     /// Checks if the integration script is already installed in the startup script.
     fn is_installed(&self) -> Result<bool>;
+
+    /// Returns the path to the profile's startup script.
+    fn script(&self) -> &path::Path;
+
+    /// Removes the integration script from the startup script.
+    ///
+    /// A copy of the original startup script is made before it is modified, and the path to that
+    /// backup is returned.
+    fn uninstall(&self) -> Result<path::PathBuf>;
+}
+
+/// Writes the printed shell code to the output stream of the application.
+struct Output<'a>(&'a Application);
+
+impl Write for Output<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.output().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.output().flush()
+    }
+}
+
+/// Removes every environment variable the application has set in this session.
+///
+/// The variables recorded by [`set_tracked_var`] are removed from the shell environment, followed
+/// by the variable used to track them.
+pub fn clear_tracked_vars(environment: &mut dyn Environment) -> Result<()> {
+    for name in get_tracked_vars() {
+        environment.unset_var(&name)?;
+    }
+
+    environment.unset_var(TRACKED_VARS)?;
+
+    env::remove_var(TRACKED_VARS);
+
+    Ok(())
+}
+
+/// Attempts to determine the name of the shell the application was run from.
+///
+/// The name of the parent process is checked first since it is the shell that is actually in
+/// use, followed by the `SHELL` environment variable which is the user's login shell. The name
+/// found is mapped to one of the names in [`SHELLS`]. On Windows, PowerShell is assumed if neither
+/// is recognized. If the shell could not be determined, or it is not supported, [`None`] is
+/// returned.
+pub fn detect_shell() -> Option<&'static str> {
+    let detected = get_parent_name()
+        .and_then(|name| to_shell_name(&name))
+        .or_else(|| env::var("SHELL").ok().and_then(|name| to_shell_name(&name)));
+
+    if detected.is_none() && cfg!(windows) {
+        return Some("powershell");
+    }
+
+    detected
+}
+
+/// Generates the completion script for the command line interface using the given generator.
+fn generate_completions<G: clap_complete::Generator>(generator: G) -> String {
+    use clap::CommandFactory;
+
+    let mut command = Application::command();
+    let mut buffer = Vec::new();
+
+    clap_complete::generate(generator, &mut command, "aws-login", &mut buffer);
+
+    String::from_utf8_lossy(&buffer).into_owned()
 }
 
 /// Returns the [`Environment`] implementation best suited for the current shell environment.
 ///
 /// This function will use the `AWS_LOGIN_SHELL` environment variable to determine which shell
-/// support module should be used to manage the environment. If the value does not correspond
-/// to any supported shell, [`None`] is returned.
+/// support module should be used to manage the environment, and the `AWS_LOGIN_SCRIPT` environment
+/// variable to determine where the shell code is written. If either is not set, the script could
+/// not be opened, or the shell is not supported, [`None`] is returned.
 pub fn get_env() -> Option<Box<dyn Environment>> {
-    match env::var(SHELL_NAME).as_deref() {
-        Ok("bash") => Some(Box::new(bash::Environment::default())),
-        Ok("fish") => Some(Box::new(fish::Environment::default())),
-        Ok("powershell") => Some(Box::new(powershell::Environment::default())),
-        Ok("zsh") => Some(Box::new(zsh::Environment::default())),
-        _ => None,
-    }
+    let shell = env::var(SHELL_NAME).ok()?;
+    let script = env::var(SCRIPT_PATH).ok()?;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(script)
+        .ok()?;
+
+    new_env(&shell, Box::new(file))
+}
+
+/// Returns the [`Environment`] implementation for the specified shell that prints to the output
+/// stream of the application.
+///
+/// The shell code printed is meant to be evaluated by the caller, which allows the environment to
+/// be modified without the shell integration (e.g. `eval "$(aws-login use --print -s bash)"`). If
+/// the specified shell is not supported, [`None`] is returned.
+pub fn get_print_env<'a>(
+    shell: &str,
+    context: &'a Application,
+) -> Option<Box<dyn Environment + 'a>> {
+    new_env(shell, Box::new(Output(context)))
 }
 
 /// Return the [`Setup`] implementation best suited for the specified shell.
@@ -64,9 +215,247 @@ pub fn get_env() -> Option<Box<dyn Environment>> {
 pub fn get_setup(shell: &str, profile: Option<&str>) -> Option<Box<dyn Setup>> {
     match shell {
         "bash" => Some(Box::new(bash::Setup::new(profile))),
+        "elvish" => Some(Box::new(elvish::Setup::new(profile))),
         "fish" => Some(Box::new(fish::Setup::new(profile))),
+        "nu" => Some(Box::new(nu::Setup::new(profile))),
         "powershell" => Some(Box::new(powershell::Setup::new(profile))),
+        "tcsh" => Some(Box::new(tcsh::Setup::new(profile))),
+        "xonsh" => Some(Box::new(xonsh::Setup::new(profile))),
         "zsh" => Some(Box::new(zsh::Setup::new(profile))),
         _ => None,
     }
 }
+
+/// Returns the name of the parent process, if it can be determined.
+#[cfg(unix)]
+fn get_parent_name() -> Option<String> {
+    use std::process::Command;
+
+    let parent_id = std::os::unix::process::parent_id().to_string();
+
+    fs::read_to_string(path::Path::new("/proc").join(&parent_id).join("comm"))
+        .ok()
+        .or_else(|| {
+            Command::new("ps")
+                .args(["-o", "comm=", "-p", &parent_id])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        })
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+}
+
+/// Returns the name of the parent process, if it can be determined.
+#[cfg(not(unix))]
+fn get_parent_name() -> Option<String> {
+    None
+}
+
+/// Returns the names of the environment variables the application has set in this session.
+pub fn get_tracked_vars() -> Vec<String> {
+    env::var(TRACKED_VARS)
+        .map(|names| names.split_whitespace().map(|s| s.to_owned()).collect())
+        .unwrap_or_default()
+}
+
+/// Maps the name or path of a shell program to one of the names in [`SHELLS`].
+///
+/// ```
+/// assert_eq!(to_shell_name("/usr/local/bin/pwsh"), Some("powershell"));
+/// assert_eq!(to_shell_name("-bash"), Some("bash"));
+/// ```
+fn to_shell_name(program: &str) -> Option<&'static str> {
+    let name = program
+        .rsplit(|c| c == '/' || c == '\\')
+        .next()
+        .unwrap_or(program)
+        .trim_start_matches('-')
+        .to_lowercase();
+
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+
+    match name {
+        "bash" => Some("bash"),
+        "elvish" => Some("elvish"),
+        "fish" => Some("fish"),
+        "nu" | "nushell" => Some("nu"),
+        "powershell" | "pwsh" => Some("powershell"),
+        "tcsh" => Some("tcsh"),
+        "xonsh" => Some("xonsh"),
+        "zsh" => Some("zsh"),
+        _ => None,
+    }
+}
+
+/// Creates the [`Environment`] implementation for the specified shell using the destination.
+fn new_env<'a>(shell: &str, file: Box<dyn Write + 'a>) -> Option<Box<dyn Environment + 'a>> {
+    match shell {
+        "bash" => Some(Box::new(bash::Environment::new(file))),
+        "elvish" => Some(Box::new(elvish::Environment::new(file))),
+        "fish" => Some(Box::new(fish::Environment::new(file))),
+        "nu" => Some(Box::new(nu::Environment::new(file))),
+        "powershell" => Some(Box::new(powershell::Environment::new(file))),
+        "tcsh" => Some(Box::new(tcsh::Environment::new(file))),
+        "xonsh" => Some(Box::new(xonsh::Environment::new(file))),
+        "zsh" => Some(Box::new(zsh::Environment::new(file))),
+        _ => None,
+    }
+}
+
+/// Sets the value of an environment variable and records its name for later removal.
+///
+/// The list of tracked names is also updated for the current process so that multiple variables
+/// may be set before the shell environment has had a chance to evaluate the changes.
+pub fn set_tracked_var(environment: &mut dyn Environment, name: &str, value: &str) -> Result<()> {
+    let mut names = get_tracked_vars();
+
+    environment.set_var(name, value)?;
+
+    if !names.iter().any(|n| n == name) {
+        names.push(name.to_owned());
+
+        let names = names.join(" ");
+
+        environment.set_var(TRACKED_VARS, &names)?;
+
+        env::set_var(TRACKED_VARS, names);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use std::sync::Mutex;
+    use std::{cell, rc};
+
+    lazy_static::lazy_static! {
+        /// Serializes the tests that change the environment variables of the current process.
+        pub static ref ENV_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    /// Collects the shell code written by an [`Environment`].
+    #[derive(Clone, Default)]
+    struct Buffer(rc::Rc<cell::RefCell<Vec<u8>>>);
+
+    impl Buffer {
+        /// Returns the shell code written so far.
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.borrow()).into_owned()
+        }
+    }
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn generated_scripts() {
+        for shell in SHELLS {
+            let script = get_setup(shell, None).unwrap().generate_script();
+
+            assert!(!script.contains("{AWS_LOGIN"), "{}", shell);
+        }
+
+        let bash = get_setup("bash", None).unwrap().generate_script();
+
+        assert!(bash.contains("\n_aws-login() {"));
+        assert!(bash.contains("\ncomplete -F _aws_login_dynamic "));
+
+        let powershell = get_setup("powershell", None).unwrap().generate_script();
+
+        assert!(powershell.starts_with("###\n# Integrates the AWS Login command"));
+        assert!(powershell.contains(&format!(
+            ". ([scriptblock]::Create({}))",
+            quote::powershell(&generate_completions(clap_complete::shells::PowerShell))
+        )));
+    }
+
+    #[test]
+    fn shell_names() {
+        assert_eq!(to_shell_name("bash"), Some("bash"));
+        assert_eq!(to_shell_name("-zsh"), Some("zsh"));
+        assert_eq!(to_shell_name("/usr/bin/fish"), Some("fish"));
+        assert_eq!(to_shell_name("/opt/homebrew/bin/nu"), Some("nu"));
+        assert_eq!(to_shell_name("/usr/local/bin/pwsh"), Some("powershell"));
+        assert_eq!(
+            to_shell_name("C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe"),
+            Some("powershell")
+        );
+        assert_eq!(to_shell_name("/bin/csh"), None);
+        assert_eq!(to_shell_name("tcsh"), Some("tcsh"));
+        assert_eq!(to_shell_name("/usr/bin/elvish"), Some("elvish"));
+        assert_eq!(to_shell_name("xonsh"), Some("xonsh"));
+        assert_eq!(to_shell_name("/bin/ksh"), None);
+        assert_eq!(to_shell_name(""), None);
+    }
+
+    #[test]
+    fn tracked_vars() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+        let buffer = Buffer::default();
+        let mut environment = new_env("bash", Box::new(buffer.clone())).unwrap();
+
+        env::remove_var(TRACKED_VARS);
+
+        set_tracked_var(environment.as_mut(), "AWS_PROFILE", "dev").unwrap();
+        set_tracked_var(environment.as_mut(), "AWS_REGION", "us-east-1").unwrap();
+        set_tracked_var(environment.as_mut(), "AWS_PROFILE", "prod").unwrap();
+
+        assert_eq!(get_tracked_vars(), ["AWS_PROFILE", "AWS_REGION"]);
+
+        clear_tracked_vars(environment.as_mut()).unwrap();
+
+        assert!(get_tracked_vars().is_empty());
+        assert_eq!(
+            buffer.contents(),
+            "export AWS_PROFILE='dev'\n\
+             export AWS_LOGIN_VARS='AWS_PROFILE'\n\
+             export AWS_REGION='us-east-1'\n\
+             export AWS_LOGIN_VARS='AWS_PROFILE AWS_REGION'\n\
+             export AWS_PROFILE='prod'\n\
+             unset AWS_PROFILE\n\
+             unset AWS_REGION\n\
+             unset AWS_LOGIN_VARS\n"
+        );
+    }
+
+    #[test]
+    fn unset_vars() {
+        let expected = [
+            ("bash", "unset AWS_PROFILE\n"),
+            ("elvish", "unset-env AWS_PROFILE\n"),
+            ("fish", "set -e AWS_PROFILE\n"),
+            ("nu", "{\"AWS_PROFILE\":null}\n"),
+            (
+                "powershell",
+                "Remove-Item Env:AWS_PROFILE -ErrorAction SilentlyContinue\n",
+            ),
+            ("tcsh", "unsetenv AWS_PROFILE\n"),
+            ("xonsh", "${...}.pop('AWS_PROFILE', None)\n"),
+            ("zsh", "unset AWS_PROFILE\n"),
+        ];
+
+        for (shell, code) in expected {
+            let buffer = Buffer::default();
+            let mut environment = new_env(shell, Box::new(buffer.clone())).unwrap();
+
+            environment.unset_var("AWS_PROFILE").unwrap();
+
+            assert_eq!(buffer.contents(), code, "{}", shell);
+        }
+
+        let mut environment = new_env("bash", Box::new(Buffer::default())).unwrap();
+
+        assert!(environment.unset_var("AWS PROFILE").is_err());
+    }
+}
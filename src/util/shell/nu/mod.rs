@@ -0,0 +1,157 @@
+//! Provides support for integrating into Nushell.
+//!
+//! This support module will allow the application to generate a file of environment changes that
+//! is loaded once the application has exited. The location of the file will depend on the value of
+//! the `AWS_LOGIN_SCRIPT` environment variable.
+//!
+//! Since Nushell cannot evaluate shell code generated at runtime, each change is written as a line
+//! containing a JSON record that is compatible with `load-env`. A record with a `null` value is
+//! used to remove the environment variable instead.
+
+use super::{block, quote};
+use crate::util::config::{BIN_NAME, CONFIG_DIR};
+use carli::error::{Context, Error, Result};
+use std::io::Write;
+use std::{fs, path};
+
+lazy_static::lazy_static! {
+    /// The path to the file containing the initialization script.
+    ///
+    /// Nushell can only `source` files that exist when the startup script is parsed, so the
+    /// initialization script is saved to this file when the integration is installed.
+    static ref INIT_FILE: path::PathBuf = CONFIG_DIR.join("init.nu");
+}
+
+/// Manages the current Nushell environment.
+pub struct Environment<'a> {
+    /// The destination of the shell code that will be loaded.
+    file: Box<dyn Write + 'a>,
+}
+
+impl<'a> Environment<'a> {
+    /// Creates a new instance of [`Environment`] that writes to the given destination.
+    pub fn new(file: Box<dyn Write + 'a>) -> Self {
+        Self { file }
+    }
+
+    /// Writes a record containing a single environment variable change.
+    fn write_change(&mut self, name: &str, value: serde_json::Value) -> Result<()> {
+        let mut record = serde_json::Map::new();
+
+        record.insert(quote::name(name)?.to_owned(), value);
+
+        writeln!(self.file, "{}", serde_json::Value::Object(record)).map_err(Error::from)
+    }
+}
+
+impl super::Environment for Environment<'_> {
+    fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
+        self.write_change(name, value.into())
+            .context(|| "Could not set environment variable.".to_owned())
+    }
+
+    fn unset_var(&mut self, name: &str) -> Result<()> {
+        self.write_change(name, serde_json::Value::Null)
+            .context(|| "Could not unset environment variable.".to_owned())
+    }
+}
+
+/// Manages the integration of the application into a Nushell environment.
+pub struct Setup {
+    /// The path to the profile startup script.
+    script: path::PathBuf,
+}
+
+impl Setup {
+    /// Creates a new instance of [`Setup`] for managing Nushell integration.
+    pub fn new(profile: Option<&str>) -> Self {
+        let script = profile
+            .map(path::PathBuf::from)
+            .unwrap_or_else(get_default_profile);
+
+        Self { script }
+    }
+
+    /// Generates the shell code installed into the startup script.
+    fn generate_code(&self) -> String {
+        format!("source {}", quote::nu(&INIT_FILE.to_string_lossy()))
+    }
+}
+
+impl super::Setup for Setup {
+    fn generate_prompt(&self) -> Option<String> {
+        None
+    }
+
+    fn generate_script(&self) -> String {
+        include_str!("init.nu")
+            .replace("{AWS_LOGIN}", &quote::nu(&BIN_NAME))
+            .replace("{AWS_LOGIN_SHELL}", super::SHELL_NAME)
+    }
+
+    fn install(&self) -> Result<()> {
+        fs::write(&*INIT_FILE, self.generate_script())
+            .map_err(Error::from)
+            .context(|| {
+                format!(
+                    "Could not write the initialization script: {}",
+                    INIT_FILE.display()
+                )
+            })?;
+
+        block::install(&self.script, &self.generate_code())
+    }
+
+    fn is_current(&self) -> Result<bool> {
+        let current = match fs::read_to_string(&*INIT_FILE) {
+            Ok(contents) => contents == self.generate_script(),
+            Err(_) => false,
+        };
+
+        Ok(current && block::is_current(&self.script, &self.generate_code())?)
+    }
+
+    fn is_installed(&self) -> Result<bool> {
+        block::is_installed(&self.script)
+    }
+
+    fn script(&self) -> &path::Path {
+        &self.script
+    }
+
+    fn uninstall(&self) -> Result<path::PathBuf> {
+        let backup = block::uninstall(&self.script)?;
+
+        if INIT_FILE.exists() {
+            fs::remove_file(&*INIT_FILE)
+                .map_err(Error::from)
+                .context(|| {
+                    format!(
+                        "Could not remove the initialization script: {}",
+                        INIT_FILE.display()
+                    )
+                })?;
+        }
+
+        Ok(backup)
+    }
+}
+
+/// Generates the path to the default profile script location.
+fn get_default_profile() -> path::PathBuf {
+    let home = home::home_dir().expect("The home directory could not be determined.");
+
+    if cfg!(windows) {
+        home.join("AppData")
+            .join("Roaming")
+            .join("nushell")
+            .join("config.nu")
+    } else if cfg!(target_os = "macos") {
+        home.join("Library")
+            .join("Application Support")
+            .join("nushell")
+            .join("config.nu")
+    } else {
+        home.join(".config").join("nushell").join("config.nu")
+    }
+}
@@ -0,0 +1,51 @@
+
+###
+# Completes dynamic values for the AWS Login command.
+#
+# This completer will complete the names of profiles and EKS clusters by
+# asking the application for them. Everything else is completed by the
+# completer generated from the command line interface.
+##
+$AwsLoginCompleter = & {
+    # The generated completer registers itself, so the registration is replaced
+    # in this scope to capture the completer instead.
+    function Register-ArgumentCompleter {
+        param([switch] $Native, [string] $CommandName, [scriptblock] $ScriptBlock)
+
+        $ScriptBlock
+    }
+
+    . ([scriptblock]::Create({AWS_LOGIN_COMPLETER}))
+}
+
+Register-ArgumentCompleter -Native -CommandName 'aws-login' -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $Elements = @($commandAst.CommandElements | ForEach-Object { $_.Extent.Text })
+
+    # Find the word before the one being completed.
+    $Index = $Elements.Count - 1
+
+    if ($wordToComplete) {
+        $Index = $Elements.Count - 2
+    }
+
+    $Previous = if ($Index -ge 0) { $Elements[$Index] } else { '' }
+    $Kind = $null
+
+    if ($Previous -eq '--profile') {
+        $Kind = 'profiles'
+    } elseif ($Elements.Count -ge 2 -and $Elements[1] -eq 'eks' -and $Index -eq 1 -and -not $wordToComplete.StartsWith('-')) {
+        $Kind = 'clusters'
+    }
+
+    if ($Kind) {
+        & {AWS_LOGIN} complete $Kind 2> $null |
+            Where-Object { $_ -like "$wordToComplete*" } |
+            ForEach-Object { [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_) }
+
+        return
+    }
+
+    & $AwsLoginCompleter $wordToComplete $commandAst $cursorPosition
+}.GetNewClosure()
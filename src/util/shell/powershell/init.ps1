@@ -2,7 +2,7 @@
 # Integrates the AWS Login command with the shell.
 #
 # This function will be invoked instead of the command whenever you type
-# `{AWS_LOGIN}` and is responsible for evaluating any shell code it writes
+# `aws-login` and is responsible for evaluating any shell code it writes
 # to a file.
 ##
 function aws-login {
@@ -13,7 +13,7 @@ function aws-login {
     $Env:AWS_LOGIN_SCRIPT = $AwsLoginScript
     $Env:{AWS_LOGIN_SHELL} = 'powershell'
 
-    {AWS_LOGIN} @args
+    & {AWS_LOGIN} @args
 
     Remove-Item Env:AWS_LOGIN_SCRIPT
     Remove-Item Env:{AWS_LOGIN_SHELL}
@@ -25,3 +25,28 @@ function aws-login {
 
     Remove-Item $AwsLoginScript
 }
+
+###
+# Switches the AWS CLI profile when entering or leaving a project.
+#
+# The prompt will ask `aws-login` to look for a project file whenever
+# the current directory changes, which will make the profile named in it the
+# active AWS CLI profile until the project is left.
+##
+if (-not $Global:AwsLoginPrompt) {
+    $Global:AwsLoginPrompt = $function:prompt
+
+    function global:prompt {
+        $ExitCode = $Global:LASTEXITCODE
+
+        if ($Global:AwsLoginPwd -ne $PWD.Path) {
+            $Global:AwsLoginPwd = $PWD.Path
+
+            aws-login hook
+        }
+
+        $Global:LASTEXITCODE = $ExitCode
+
+        & $Global:AwsLoginPrompt
+    }
+}
@@ -0,0 +1,18 @@
+###
+# Adds the AWS Login segment to the shell prompt.
+#
+# The segment shows the active AWS CLI profile, the region, and the time left
+# before the SSO session expires. It is only added once.
+##
+if (-not $Global:AwsLoginSegmentPrompt) {
+    $Global:AwsLoginSegmentPrompt = $function:prompt
+
+    function global:prompt {
+        $ExitCode = $Global:LASTEXITCODE
+        $Segment = & {AWS_LOGIN} prompt --format '[{profile}[@{region}][ ({expiry})] ]'
+
+        $Global:LASTEXITCODE = $ExitCode
+
+        "$Segment$(& $Global:AwsLoginSegmentPrompt)"
+    }
+}
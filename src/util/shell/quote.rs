@@ -0,0 +1,477 @@
+//! Provides quoting and escaping of values for use in shell code.
+//!
+//! The shell code generated by each [`super::Environment`] is evaluated by the parent shell once
+//! the application has exited. Any value written into that code must be quoted using the rules of
+//! the shell evaluating it, otherwise a value containing characters such as `"`, `$`, or `` ` ``
+//! could break the evaluation or inject commands into the user's shell.
+
+use carli::err;
+use carli::error::Result;
+
+/// The ASCII punctuation that does not need to be escaped for the C shell.
+const CSH_SAFE: &str = "_-./:,@%+=";
+
+/// The characters PowerShell treats as single quotes.
+///
+/// In addition to the ASCII apostrophe, PowerShell will also accept the typographic single quotes
+/// as string delimiters. Each of these must be escaped by doubling it.
+const POWERSHELL_QUOTES: &[char] = &['\'', '\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}'];
+
+/// Quotes a value for the C shell (e.g. csh or tcsh).
+///
+/// Single quotes do not protect `!` from history substitution, so each character with a special
+/// meaning is escaped using a backslash instead. A newline can only be escaped inside of quotes,
+/// so it is written as a backslash followed by a newline, wrapped in single quotes.
+///
+/// ```
+/// assert_eq!(csh("it's"), "it\\'s");
+/// ```
+pub fn csh(value: &str) -> String {
+    if value.is_empty() {
+        return "''".to_owned();
+    }
+
+    let mut quoted = String::with_capacity(value.len() * 2);
+
+    for c in value.chars() {
+        if c == '\n' {
+            quoted.push_str("'\\\n'");
+        } else if c.is_ascii_alphanumeric() || !c.is_ascii() || CSH_SAFE.contains(c) {
+            quoted.push(c);
+        } else {
+            quoted.push('\\');
+            quoted.push(c);
+        }
+    }
+
+    quoted
+}
+
+/// Quotes a value for Elvish.
+///
+/// The value is wrapped in single quotes, inside of which no character has a special meaning.
+/// Any single quote inside of the value is escaped by doubling it.
+///
+/// ```
+/// assert_eq!(elvish("it's"), "'it''s'");
+/// ```
+pub fn elvish(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quotes a value for the Fish shell.
+///
+/// The value is wrapped in single quotes, which disables variable expansion and command
+/// substitution. Only backslashes and single quotes need to be escaped inside of them.
+///
+/// ```
+/// assert_eq!(fish("it's"), "'it\\'s'");
+/// ```
+pub fn fish(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('\'');
+
+    for c in value.chars() {
+        if c == '\\' || c == '\'' {
+            quoted.push('\\');
+        }
+
+        quoted.push(c);
+    }
+
+    quoted.push('\'');
+
+    quoted
+}
+
+/// Checks that an environment variable name is safe to use in shell code.
+///
+/// Names are not quoted since most shells do not support it for variable assignments. Instead,
+/// only names made of ASCII letters, digits, and underscores (and not starting with a digit) are
+/// accepted.
+///
+/// ```
+/// assert!(name("AWS_PROFILE").is_ok());
+/// assert!(name("$(id)").is_err());
+/// ```
+pub fn name(name: &str) -> Result<&str> {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(first) => {
+            (first.is_ascii_alphabetic() || first == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    };
+
+    if !valid {
+        err!(
+            1,
+            "The environment variable name, {:?}, is not valid.",
+            name
+        );
+    }
+
+    Ok(name)
+}
+
+/// Quotes a value for Nushell.
+///
+/// The value is wrapped in a raw string, inside of which no character has a special meaning. The
+/// raw string is delimited using one more `#` than the longest run of `#` following a single quote
+/// in the value, so that the value cannot terminate the string early.
+///
+/// ```
+/// assert_eq!(nu("it's"), "r#'it's'#");
+/// ```
+pub fn nu(value: &str) -> String {
+    let mut longest = 0;
+    let mut run = None;
+
+    for c in value.chars() {
+        run = match (c, run) {
+            ('\'', _) => Some(0),
+            ('#', Some(n)) => Some(n + 1),
+            _ => None,
+        };
+
+        if let Some(n) = run {
+            longest = longest.max(n);
+        }
+    }
+
+    let hashes = "#".repeat(longest + 1);
+
+    format!("r{}'{}'{}", hashes, value, hashes)
+}
+
+/// Quotes a value for a POSIX compatible shell (e.g. Bash or Zsh).
+///
+/// The value is wrapped in single quotes, inside of which no character has a special meaning.
+/// Since a single quote cannot appear inside of single quotes, each one is replaced by closing
+/// the quotes, adding an escaped single quote, and then re-opening the quotes.
+///
+/// ```
+/// assert_eq!(posix("it's"), "'it'\\''s'");
+/// ```
+pub fn posix(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Quotes a value for PowerShell.
+///
+/// The value is wrapped in single quotes, which disables variable expansion and subexpressions.
+/// Any single quote inside of the value is escaped by doubling it.
+///
+/// ```
+/// assert_eq!(powershell("it's"), "'it''s'");
+/// ```
+pub fn powershell(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('\'');
+
+    for c in value.chars() {
+        if POWERSHELL_QUOTES.contains(&c) {
+            quoted.push(c);
+        }
+
+        quoted.push(c);
+    }
+
+    quoted.push('\'');
+
+    quoted
+}
+
+/// Quotes a value for xonsh.
+///
+/// Xonsh evaluates Python expressions, so the value is written as a Python string literal wrapped
+/// in single quotes. Backslashes, single quotes, and control characters are escaped so that the
+/// literal cannot be terminated early or span multiple lines.
+///
+/// ```
+/// assert_eq!(xonsh("it's"), "'it\\'s'");
+/// ```
+pub fn xonsh(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('\'');
+
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('\'');
+
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::process::Command;
+    use which::which;
+
+    /// Strings that would break the evaluation or inject commands if not quoted correctly.
+    const HOSTILE: &[&str] = &[
+        "",
+        "plain",
+        "with spaces",
+        "double \" quote",
+        "single ' quote",
+        "''",
+        "back\\slash",
+        "trailing\\",
+        "\\'",
+        "$HOME",
+        "${HOME}",
+        "$(echo injected)",
+        "`echo injected`",
+        "(echo injected)",
+        "; echo injected",
+        "&& echo injected",
+        "| echo injected",
+        "line\nbreak",
+        "carriage\rreturn",
+        "tab\tbed",
+        "glob *",
+        "~",
+        "!event",
+        "%s%n",
+        "-n",
+        "$Env:PATH",
+        "@(1, 2)",
+        "‘smart’ ‚quotes‛",
+        "unicode: ñ 日本 🎉",
+    ];
+
+    /// Evaluates the script with the shell and returns what it printed to standard output.
+    fn evaluate(shell: &str, args: &[&str], script: &str) -> String {
+        let output = Command::new(shell)
+            .args(args)
+            .arg(script)
+            .output()
+            .expect("Could not run the shell.");
+
+        assert!(
+            output.status.success(),
+            "{}: {}",
+            script,
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        String::from_utf8(output.stdout).expect("The output is not valid UTF-8.")
+    }
+
+    /// Checks if a shell is installed, so that its round trip is skipped where it is not.
+    fn installed(shell: &str) -> bool {
+        let installed = which(shell).is_ok();
+
+        if !installed {
+            eprintln!("Skipping the round trip, {} is not installed.", shell);
+        }
+
+        installed
+    }
+
+    /// Checks that each hostile string survives a round trip through the given POSIX shell.
+    fn round_trip_posix(shell: &str) {
+        for value in HOSTILE {
+            let script = format!(
+                "export TEST_VALUE={}\nprintf '%s' \"$TEST_VALUE\"",
+                posix(value)
+            );
+
+            assert_eq!(&evaluate(shell, &["-c"], &script), value);
+        }
+    }
+
+    #[test]
+    fn quote_csh() {
+        assert_eq!(csh(""), "''");
+        assert_eq!(csh("a b"), "a\\ b");
+        assert_eq!(csh("it's"), "it\\'s");
+        assert_eq!(csh("!event"), "\\!event");
+        assert_eq!(csh("a\nb"), "a'\\\n'b");
+        assert_eq!(csh("$(id)"), "\\$\\(id\\)");
+    }
+
+    #[test]
+    fn quote_elvish() {
+        assert_eq!(elvish(""), "''");
+        assert_eq!(elvish("a b"), "'a b'");
+        assert_eq!(elvish("it's"), "'it''s'");
+        assert_eq!(elvish("a\\b"), "'a\\b'");
+        assert_eq!(elvish("$(id)"), "'$(id)'");
+    }
+
+    #[test]
+    fn quote_fish() {
+        assert_eq!(fish(""), "''");
+        assert_eq!(fish("a b"), "'a b'");
+        assert_eq!(fish("it's"), "'it\\'s'");
+        assert_eq!(fish("a\\b"), "'a\\\\b'");
+        assert_eq!(fish("$(id)"), "'$(id)'");
+    }
+
+    #[test]
+    fn quote_nu() {
+        assert_eq!(nu(""), "r#''#");
+        assert_eq!(nu("a b"), "r#'a b'#");
+        assert_eq!(nu("it's"), "r#'it's'#");
+        assert_eq!(nu("a'#b"), "r##'a'#b'##");
+        assert_eq!(nu("$(id)"), "r#'$(id)'#");
+    }
+
+    #[test]
+    fn quote_posix() {
+        assert_eq!(posix(""), "''");
+        assert_eq!(posix("a b"), "'a b'");
+        assert_eq!(posix("it's"), "'it'\\''s'");
+        assert_eq!(posix("a\\b"), "'a\\b'");
+        assert_eq!(posix("$(id)"), "'$(id)'");
+    }
+
+    #[test]
+    fn quote_powershell() {
+        assert_eq!(powershell(""), "''");
+        assert_eq!(powershell("a b"), "'a b'");
+        assert_eq!(powershell("it's"), "'it''s'");
+        assert_eq!(powershell("‘a’"), "'‘‘a’’'");
+        assert_eq!(powershell("$(id)"), "'$(id)'");
+    }
+
+    #[test]
+    fn quote_xonsh() {
+        assert_eq!(xonsh(""), "''");
+        assert_eq!(xonsh("a b"), "'a b'");
+        assert_eq!(xonsh("it's"), "'it\\'s'");
+        assert_eq!(xonsh("a\\b"), "'a\\\\b'");
+        assert_eq!(xonsh("a\nb"), "'a\\nb'");
+        assert_eq!(xonsh("\u{7}"), "'\\u0007'");
+        assert_eq!(xonsh("$(id)"), "'$(id)'");
+    }
+
+    #[test]
+    fn round_trip_bash() {
+        round_trip_posix("bash");
+    }
+
+    #[test]
+    #[ignore = "requires Elvish"]
+    fn round_trip_elvish() {
+        for value in HOSTILE {
+            let script = format!("set-env TEST_VALUE {}\nprint $E:TEST_VALUE", elvish(value));
+
+            assert_eq!(&evaluate("elvish", &["-norc", "-c"], &script), value);
+        }
+    }
+
+    #[test]
+    fn round_trip_fish() {
+        if !installed("fish") {
+            return;
+        }
+
+        for value in HOSTILE {
+            let script = format!(
+                "set -gx TEST_VALUE {}\nprintf '%s' \"$TEST_VALUE\"",
+                fish(value)
+            );
+
+            assert_eq!(&evaluate("fish", &["-c"], &script), value);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires Nushell"]
+    fn round_trip_nu() {
+        for value in HOSTILE {
+            let script = format!("print --no-newline {}", nu(value));
+
+            assert_eq!(&evaluate("nu", &["-c"], &script), value);
+        }
+    }
+
+    #[test]
+    fn round_trip_powershell() {
+        let shell = match which("pwsh").or_else(|_| which("powershell")) {
+            Ok(shell) => shell.to_string_lossy().to_string(),
+            Err(_) => {
+                eprintln!("Skipping the round trip, PowerShell is not installed.");
+
+                return;
+            }
+        };
+
+        for value in HOSTILE {
+            let script = format!(
+                "[Console]::OutputEncoding = [Text.Encoding]::UTF8\n$Env:TEST_VALUE = {}\n[Console]::Out.Write($Env:TEST_VALUE)",
+                powershell(value)
+            );
+
+            assert_eq!(
+                &evaluate(
+                    &shell,
+                    &["-NoProfile", "-NonInteractive", "-Command"],
+                    &script
+                ),
+                value
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "requires tcsh"]
+    fn round_trip_tcsh() {
+        for value in HOSTILE {
+            let script = format!("setenv TEST_VALUE {}\nprintenv TEST_VALUE", csh(value));
+            let output = evaluate("tcsh", &["-f", "-c"], &script);
+
+            assert_eq!(output.strip_suffix('\n').unwrap_or(&output), *value);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires xonsh"]
+    fn round_trip_xonsh() {
+        for value in HOSTILE {
+            let script = format!("$TEST_VALUE = {}\nprint($TEST_VALUE, end='')", xonsh(value));
+
+            assert_eq!(&evaluate("xonsh", &["--no-rc", "-c"], &script), value);
+        }
+    }
+
+    #[test]
+    fn round_trip_zsh() {
+        if !installed("zsh") {
+            return;
+        }
+
+        round_trip_posix("zsh");
+    }
+
+    #[test]
+    fn valid_names() {
+        assert!(name("AWS_PROFILE").is_ok());
+        assert!(name("_private").is_ok());
+        assert!(name("a1").is_ok());
+
+        assert!(name("").is_err());
+        assert!(name("1a").is_err());
+        assert!(name("A B").is_err());
+        assert!(name("A=B").is_err());
+        assert!(name("$(id)").is_err());
+        assert!(name("A;B").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Provides support for integrating into the C shell (tcsh).
+//!
+//! This support module will allow the application to generate a shell script that is sourced
+//! once the application has exited. The location of the script will depend on the value of the
+//! `AWS_LOGIN_SCRIPT` environment variable.
+
+use super::{block, quote};
+use crate::util::config::{BIN_NAME, CONFIG_DIR};
+use carli::error::{Context, Error, Result};
+use std::io::Write;
+use std::{fs, path};
+
+lazy_static::lazy_static! {
+    /// The path to the file containing the initialization script.
+    ///
+    /// The C shell collapses the output of command substitution into a single line, which cannot
+    /// be evaluated, so the initialization script is saved to this file when the integration is
+    /// installed.
+    static ref INIT_FILE: path::PathBuf = CONFIG_DIR.join("init.tcsh");
+}
+
+/// Manages the current tcsh environment.
+pub struct Environment<'a> {
+    /// The destination of the shell code that will be evaluated.
+    file: Box<dyn Write + 'a>,
+}
+
+impl<'a> Environment<'a> {
+    /// Creates a new instance of [`Environment`] that writes to the given destination.
+    pub fn new(file: Box<dyn Write + 'a>) -> Self {
+        Self { file }
+    }
+}
+
+impl super::Environment for Environment<'_> {
+    fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
+        writeln!(
+            self.file,
+            "setenv {} {}",
+            quote::name(name)?,
+            quote::csh(value)
+        )
+        .map_err(Error::from)
+        .context(|| "Could not set environment variable.".to_owned())
+    }
+
+    fn unset_var(&mut self, name: &str) -> Result<()> {
+        writeln!(self.file, "unsetenv {}", quote::name(name)?)
+            .map_err(Error::from)
+            .context(|| "Could not unset environment variable.".to_owned())
+    }
+}
+
+/// Manages the integration of the application into a tcsh environment.
+pub struct Setup {
+    /// The path to the profile startup script.
+    script: path::PathBuf,
+}
+
+impl Setup {
+    /// Creates a new instance of [`Setup`] for managing tcsh integration.
+    pub fn new(profile: Option<&str>) -> Self {
+        let script = profile
+            .map(path::PathBuf::from)
+            .unwrap_or_else(get_default_profile);
+
+        Self { script }
+    }
+
+    /// Generates the shell code installed into the startup script.
+    ///
+    /// The `.cshrc` startup script is also read by csh, which does not support the initialization
+    /// script, so it is only sourced by tcsh.
+    fn generate_code(&self) -> String {
+        format!(
+            "if ( $?tcsh ) source {}",
+            quote::csh(&INIT_FILE.to_string_lossy())
+        )
+    }
+}
+
+impl super::Setup for Setup {
+    fn generate_prompt(&self) -> Option<String> {
+        None
+    }
+
+    fn generate_script(&self) -> String {
+        include_str!("init.tcsh")
+            .replace("{AWS_LOGIN}", &quote::csh(&BIN_NAME))
+            .replace("{AWS_LOGIN_SHELL}", super::SHELL_NAME)
+    }
+
+    fn install(&self) -> Result<()> {
+        fs::write(&*INIT_FILE, self.generate_script())
+            .map_err(Error::from)
+            .context(|| {
+                format!(
+                    "Could not write the initialization script: {}",
+                    INIT_FILE.display()
+                )
+            })?;
+
+        block::install(&self.script, &self.generate_code())
+    }
+
+    fn is_current(&self) -> Result<bool> {
+        let current = match fs::read_to_string(&*INIT_FILE) {
+            Ok(contents) => contents == self.generate_script(),
+            Err(_) => false,
+        };
+
+        Ok(current && block::is_current(&self.script, &self.generate_code())?)
+    }
+
+    fn is_installed(&self) -> Result<bool> {
+        block::is_installed(&self.script)
+    }
+
+    fn script(&self) -> &path::Path {
+        &self.script
+    }
+
+    fn uninstall(&self) -> Result<path::PathBuf> {
+        let backup = block::uninstall(&self.script)?;
+
+        if INIT_FILE.exists() {
+            fs::remove_file(&*INIT_FILE)
+                .map_err(Error::from)
+                .context(|| {
+                    format!(
+                        "Could not remove the initialization script: {}",
+                        INIT_FILE.display()
+                    )
+                })?;
+        }
+
+        Ok(backup)
+    }
+}
+
+/// Generates the path to the default profile script location.
+///
+/// The tcsh shell will read `.tcshrc` if it exists, falling back to `.cshrc` otherwise. If only
+/// `.cshrc` exists, it is used so that the integration is read by the shell.
+fn get_default_profile() -> path::PathBuf {
+    let home = home::home_dir().expect("The home directory could not be determined.");
+    let tcshrc = home.join(".tcshrc");
+    let cshrc = home.join(".cshrc");
+
+    if !tcshrc.exists() && cshrc.exists() {
+        cshrc
+    } else {
+        tcshrc
+    }
+}
@@ -0,0 +1,30 @@
+
+###
+# Completes dynamic values for the AWS Login command.
+#
+# This function will complete the names of profiles and EKS clusters by asking
+# the application for them. Everything else is completed by the function
+# generated from the command line interface.
+##
+_aws_login_dynamic()
+{
+    local KIND=
+
+    if [ "${words[CURRENT-1]}" = "--profile" ]; then
+        KIND=profiles
+    elif [ "${words[2]}" = "eks" ] && [ "$CURRENT" -eq 3 ] && [[ "${words[CURRENT]}" != -* ]]; then
+        KIND=clusters
+    fi
+
+    if [ -n "$KIND" ]; then
+        compadd -- ${(f)"$({AWS_LOGIN} complete "$KIND" 2> /dev/null)"}
+
+        return 0
+    fi
+
+    _aws-login "$@"
+}
+
+if (( $+functions[compdef] )); then
+    compdef _aws_login_dynamic aws-login
+fi
@@ -4,7 +4,7 @@
 # Integrates the AWS Login command with the shell.
 #
 # This function will be invoked instead of the command whenever you type
-# `{AWS_LOGIN}` and is responsible for evaluating any shell code it writes
+# `aws-login` and is responsible for evaluating any shell code it writes
 # to a file.
 ##
 aws-login()
@@ -15,7 +15,7 @@ aws-login()
     if AWS_LOGIN_SCRIPT="$(mktemp)"; then
 
         # Execute the real command.
-        AWS_LOGIN_SCRIPT="$AWS_LOGIN_SCRIPT" {AWS_LOGIN_SHELL}=zsh "{AWS_LOGIN}" "$@"
+        AWS_LOGIN_SCRIPT="$AWS_LOGIN_SCRIPT" {AWS_LOGIN_SHELL}=zsh {AWS_LOGIN} "$@"
 
         local STATUS=$?
 
@@ -31,3 +31,20 @@ aws-login()
 
     return 1
 }
+
+###
+# Switches the AWS CLI profile when entering or leaving a project.
+#
+# This function will ask `aws-login` to look for a project file whenever
+# the current directory changes, which will make the profile named in it the
+# active AWS CLI profile until the project is left.
+##
+_aws_login_hook()
+{
+    aws-login hook
+}
+
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _aws_login_hook
+
+_aws_login_hook
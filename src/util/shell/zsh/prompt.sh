@@ -0,0 +1,13 @@
+###
+# Adds the AWS Login segment to the shell prompt.
+#
+# The segment shows the active AWS CLI profile, the region, and the time left
+# before the SSO session expires. It is only added once.
+##
+setopt PROMPT_SUBST
+
+_AWS_LOGIN_BIN={AWS_LOGIN}
+
+if [[ "$PROMPT" != *" prompt --shell zsh "* ]]; then
+    PROMPT='$("$_AWS_LOGIN_BIN" prompt --shell zsh --format "[{profile}[@{region}][ ({expiry})] ]")'"$PROMPT"
+fi
@@ -4,26 +4,45 @@ use carli::err;
 use carli::error::Result;
 use std::fmt;
 
+/// Restores the terminal to its normal state.
+///
+/// If the application is stopped while the user is being prompted, the terminal could be left in
+/// raw mode with the cursor hidden. This undoes both, and does nothing if the terminal is already
+/// in its normal state.
+pub fn restore() {
+    use crossterm::{cursor, execute, terminal};
+    use std::io;
+
+    if let Ok(true) = terminal::is_raw_mode_enabled() {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), cursor::Show);
+    }
+}
+
 /// Prompts the user to select an item from a list.
 ///
 /// This function will draw an interface that will display a prompt, followed by a list of items
 /// for the user to select. Once the user has made their selection, the selected option will be
-/// returned.
+/// returned. If `auto` is `true` and there is only one item, it is returned without prompting.
 ///
 /// ```
 /// use crate::util::term::select;
 ///
 /// let choices = vec!["a", "b", "c"];
-/// let selected = select("Please choose a letter:", &choices);
+/// let selected = select("Please choose a letter:", &choices, false);
 ///
 /// println!("You chose: {}", selected);
 /// ```
-pub fn select<'a, 'b, T>(prompt: &'a str, list: &'b [T]) -> Result<&'b T>
+pub fn select<'a, T>(prompt: &str, list: &'a [T], auto: bool) -> Result<&'a T>
 where
     T: fmt::Display,
 {
     use requestty::{prompt_one, ErrorKind, Question};
 
+    if let (true, [only]) = (auto, list) {
+        return Ok(only);
+    }
+
     let compatible = list
         .iter()
         .map(|i| format!("{}", i))